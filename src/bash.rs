@@ -1,44 +1,146 @@
 use std::fmt::Write;
 
 use complgen::StateId;
+
 use crate::error::Result;
 use crate::dfa::DFA;
+use crate::regex::{Input, MatchAnythingInput};
+use crate::codegen_common::{as_regex_pattern, collect_reachable_states};
 
 
 fn write_dfa_state<W: Write>(buffer: &mut W, dfa: &DFA, state: StateId) -> Result<()> {
     write!(buffer, r#"
 _state_{state} () {{
-    case ${{COMP_WORDS[$current_dfa_word]}}
+    if [[ $current_dfa_word -eq $COMP_CWORD ]]; then
+        local cur="${{COMP_WORDS[$COMP_CWORD]}}"
 "#, state = state)?;
 
+    for (input, _) in dfa.get_transitions_from(state) {
+        match input {
+            Input::Literal(literal, _) => {
+                write!(buffer, r#"        COMPREPLY+=($(compgen -W "{literal}" -- "$cur"))
+"#, literal = literal)?;
+            },
+            Input::Any(MatchAnythingInput::Nonterminal(nonterm)) if nonterm.as_str() == "PATH" => {
+                write!(buffer, r#"        COMPREPLY+=($(compgen -A file -- "$cur"))
+"#)?;
+            },
+            Input::Any(MatchAnythingInput::Nonterminal(nonterm)) if nonterm.as_str() == "DIRECTORY" => {
+                write!(buffer, r#"        COMPREPLY+=($(compgen -A directory -- "$cur"))
+"#)?;
+            },
+            Input::Any(MatchAnythingInput::Command(command)) => {
+                write!(buffer, r#"        COMPREPLY+=($(compgen -W "$({command})" -- "$cur"))
+"#, command = command)?;
+            },
+            // A regex-typed nonterminal has no enumerable candidate source (see complete.rs's
+            // get_completions_for_input), so it's excluded here and falls through to the plain
+            // "nothing to offer" arm below, same as one with no registered builtin.
+            Input::Any(MatchAnythingInput::Nonterminal(nonterm)) if as_regex_pattern(nonterm.as_str()).is_none() => {
+                match crate::complete::BUILTIN_NONTERMINALS.iter().find(|builtin| builtin.name == nonterm.as_str()) {
+                    Some(builtin) => {
+                        write!(buffer, r#"        COMPREPLY+=($({bash} -- "$cur"))
+"#, bash = builtin.bash)?;
+                    },
+                    None => {
+                        // No built-in completer for this nonterminal; nothing to offer.
+                    },
+                }
+            },
+            Input::Any(MatchAnythingInput::Nonterminal(_)) => {
+                // No built-in completer for this nonterminal; nothing to offer.
+            },
+        }
+    }
+
+    // bash only exposes "no trailing space" as a per-invocation `compopt -o nospace`, not a
+    // per-candidate flag, so it's only applied when it's unambiguous: every literal gathered
+    // above ends with '=' (e.g. a state offering only `--foo=`-shaped options), matching
+    // complete.rs's `literal.ends_with('=')` rule for the dynamic path.
+    let literals_at_cursor: Vec<_> = dfa.get_transitions_from(state).into_iter().filter_map(|(input, _)| match input {
+        Input::Literal(literal, _) => Some(literal),
+        _ => None,
+    }).collect();
+    if !literals_at_cursor.is_empty() && literals_at_cursor.iter().all(|literal| literal.ends_with('=')) {
+        write!(buffer, r#"        compopt -o nospace
+"#)?;
+    }
+
+    write!(buffer, r#"        return
+    fi
+
+    case "${{COMP_WORDS[$current_dfa_word]}}" in
+"#)?;
+
+    // A DFA state may have several `matches_anything()` transitions (e.g. one for a
+    // <PATH> and one for a nonterminal), but bash's `case` only ever runs the first
+    // arm that matches, so at most one catch-all `*)` arm is emitted here.
+    let mut regex_destination: Option<(String, StateId)> = None;
+    let mut any_destination: Option<StateId> = None;
     for (input, to) in dfa.get_transitions_from(state) {
-        write!(buffer, r#"
-        {input})
+        match input {
+            Input::Literal(literal, _) => {
+                write!(buffer, r#"        {literal})
             current_dfa_word=$((current_dfa_word+1))
-            _state_{to};;
-"#, input = input)?;
+            _state_{to}
+            return
+            ;;
+"#, literal = literal, to = to)?;
+            },
+            Input::Any(MatchAnythingInput::Nonterminal(nonterm)) if as_regex_pattern(nonterm.as_str()).is_some() => {
+                let pattern = as_regex_pattern(nonterm.as_str()).unwrap().to_string();
+                regex_destination.get_or_insert((pattern, to));
+            },
+            Input::Any(_) => {
+                any_destination.get_or_insert(to);
+            },
+        }
     }
 
-    write!(buffer, r#"
-    esac
+    if regex_destination.is_some() || any_destination.is_some() {
+        write!(buffer, r#"        *)
+"#)?;
+        if let Some((pattern, to)) = &regex_destination {
+            // bash's `=~` is POSIX ERE, which has no `(?:...)`; a plain `(...)` group is fine
+            // since nothing here needs to capture.
+            write!(buffer, r#"            if [[ "${{COMP_WORDS[$current_dfa_word]}}" =~ ^({pattern})$ ]]; then
+                current_dfa_word=$((current_dfa_word+1))
+                _state_{to}
+                return
+            fi
+"#, pattern = pattern, to = to)?;
+        }
+        if let Some(to) = any_destination {
+            write!(buffer, r#"            current_dfa_word=$((current_dfa_word+1))
+            _state_{to}
+            return
+"#, to = to)?;
+        }
+        write!(buffer, r#"            ;;
+"#)?;
+    }
+
+    write!(buffer, r#"    esac
 }}
 "#)?;
     Ok(())
 }
 
 
-pub fn write_completion_script<W: Write>(buffer: &mut W, command: &str, _dfa: &DFA) -> Result<()> {
-    // TODO Write a separate bash function for each state in a DFA
+pub fn write_completion_script<W: Write>(buffer: &mut W, command: &str, dfa: &DFA) -> Result<()> {
+    for state in collect_reachable_states(dfa) {
+        write_dfa_state(buffer, dfa, state)?;
+    }
 
     write!(buffer, r#"
 _{command}_completions () {{
-  COMPREPLY+=("now")
-  COMPREPLY+=("tomorrow")
-  COMPREPLY+=("never")
+    local current_dfa_word=1
+    COMPREPLY=()
+    _state_{starting_state}
 }}
 
 complete -F _{command}_completions {command}
-"#, command = command)?;
+"#, command = command, starting_state = dfa.starting_state)?;
 
     Ok(())
-}
\ No newline at end of file
+}
@@ -0,0 +1,33 @@
+use complgen::StateId;
+use hashbrown::HashSet;
+
+use crate::dfa::DFA;
+
+// Until `Input`/`MatchAnythingInput` (crate::regex) grow a first-class regex variant, a
+// `<NAME> ::= /pattern/;` nonterminal is compiled down to an `Input::Any` whose name is the
+// pattern source, prefixed with this sentinel. grammar.rs rejects a grammar-authored symbol
+// name starting with it, so this prefix can only ever originate from that lowering step.
+pub(crate) const REGEX_NONTERMINAL_PREFIX: &str = "regex:";
+
+pub(crate) fn as_regex_pattern(nonterm: &str) -> Option<&str> {
+    nonterm.strip_prefix(REGEX_NONTERMINAL_PREFIX)
+}
+
+// Collect every state reachable from the starting state, in the order they're first visited,
+// so that a `_state_N` function gets emitted for each one (including states with no outgoing
+// transitions, which are still valid tail-call targets).
+pub(crate) fn collect_reachable_states(dfa: &DFA) -> Vec<StateId> {
+    let mut visited: HashSet<StateId> = Default::default();
+    let mut stack = vec![dfa.starting_state];
+    let mut order: Vec<StateId> = Default::default();
+    while let Some(state) = stack.pop() {
+        if !visited.insert(state) {
+            continue;
+        }
+        order.push(state);
+        for (_, to) in dfa.get_transitions_from(state) {
+            stack.push(to);
+        }
+    }
+    order
+}
@@ -1,4 +1,5 @@
 use std::io::Write;
+use std::path::Path;
 use std::process::Command;
 
 use complgen::StateId;
@@ -6,16 +7,43 @@ use hashbrown::HashMap;
 
 use ustr::ustr;
 use anyhow::{anyhow, Context};
+use clap::ValueEnum;
 
 use crate::{dfa::DFA, regex::{Input, MatchAnythingInput}};
+use crate::codegen_common::as_regex_pattern;
+
+// `word` must fully satisfy `pattern` (anchored at both ends) -- used to validate an
+// already-*completed* word, where "is this a valid value" is unambiguous. A pattern that
+// failed to compile can't happen here (grammar.rs rejects a malformed one at parse time), so
+// fail open rather than block completion on it.
+fn regex_fully_matches(pattern: &str, word: &str) -> bool {
+    regex::Regex::new(&format!("^(?:{pattern})$")).map(|re| re.is_match(word)).unwrap_or(true)
+}
 
+/// A single completion candidate. `no_space` mirrors clap_complete's `--no-space`: when set,
+/// the shell should not append a trailing space after inserting `value` (e.g. a directory
+/// prefix the user will likely keep typing, or a `--opt=` flag waiting on its value).
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Completion {
+    pub value: String,
+    pub description: String,
+    pub no_space: bool,
+}
 
+impl Completion {
+    fn new(value: String, description: String, no_space: bool) -> Self {
+        Self { value, description, no_space }
+    }
+}
 
-#[derive(Debug, Clone, Copy)]
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
 pub enum Shell {
     Bash,
     Fish,
     Zsh,
+    Elvish,
+    Powershell,
 }
 
 
@@ -60,6 +88,8 @@ impl Shell {
             Shell::Bash => Command::new("bash").arg("-c").arg(command).output()?,
             Shell::Fish => Command::new("fish").arg("-c").arg(command).output()?,
             Shell::Zsh => Command::new("zsh").arg("-c").arg(command).output()?,
+            Shell::Elvish => Command::new("elvish").arg("-c").arg(command).output()?,
+            Shell::Powershell => Command::new("pwsh").arg("-Command").arg(command).output()?,
         };
 
         if !output.status.success() {
@@ -81,6 +111,8 @@ impl Shell {
             Shell::Bash => self.shell_out(&format!("compgen -A file {prefix}"))?,
             Shell::Fish => self.shell_out(&format!("__fish_complete_path {prefix}"))?,
             Shell::Zsh => capture_zsh_completions("_path_files", &format!("dummy {prefix}"))?,
+            Shell::Elvish => self.shell_out(&format!("put (edit:complete-filename {prefix})"))?,
+            Shell::Powershell => self.shell_out(&format!("Get-ChildItem -Path \"{prefix}*\" | Select-Object -ExpandProperty Name"))?,
         };
         Ok(result)
     }
@@ -90,11 +122,56 @@ impl Shell {
             Shell::Bash => self.shell_out(&format!("compgen -A directory {prefix}")),
             Shell::Fish => self.shell_out(&format!("__fish_complete_directories {prefix}")),
             Shell::Zsh => self.shell_out(&format!(r#"printf "%s\n" {prefix}*(/)"#)),
+            // `edit:complete-filename` suffixes directory candidates with `/`; filter down to
+            // those instead of indexing out just the first candidate of any kind.
+            Shell::Elvish => self.shell_out(&format!("edit:complete-filename {prefix} | each {{|c| if (str:has-suffix $c /) {{ put $c }} }}")),
+            Shell::Powershell => self.shell_out(&format!("Get-ChildItem -Path \"{prefix}*\" -Directory | Select-Object -ExpandProperty Name")),
         }
     }
+
+    // Completion commands for well-known nonterminals, so grammar authors can write e.g.
+    // `kill <PID>` or `ssh <HOST>` and get native completions without shelling out a custom
+    // `{ ... }` command themselves.
+    fn complete_builtin_nonterminal(&self, nonterm: &str, prefix: &str) -> Option<anyhow::Result<String>> {
+        let command = BUILTIN_NONTERMINALS.iter().find(|n| n.name == nonterm)?;
+        let result = match self {
+            Shell::Bash => self.shell_out(&format!("{} -- {prefix}", command.bash)),
+            Shell::Fish => self.shell_out(&format!("{} {prefix}", command.fish)),
+            Shell::Zsh => capture_zsh_completions(command.zsh, &format!("dummy {prefix}")),
+            Shell::Elvish => self.shell_out(command.elvish),
+            Shell::Powershell => self.shell_out(command.powershell),
+        };
+        Some(result)
+    }
 }
 
 
+// Exposed `pub(crate)` so the static per-shell generators (bash.rs etc.) can emit the same
+// builtin commands the dynamic `complete_builtin_nonterminal` path above shells out to,
+// instead of offering nothing for e.g. `kill <PID>` in a generated script.
+pub(crate) struct BuiltinNonterminal {
+    pub(crate) name: &'static str,
+    /// A full `compgen` invocation, minus the trailing `-- <word>` appended at the call site,
+    /// since not every one of these is backed by a `compgen -A <action>` action (`PID` isn't:
+    /// bash has no `process` action, so it sources a wordlist from `ps` instead).
+    pub(crate) bash: &'static str,
+    pub(crate) fish: &'static str,
+    pub(crate) zsh: &'static str,
+    pub(crate) elvish: &'static str,
+    pub(crate) powershell: &'static str,
+}
+
+
+pub(crate) const BUILTIN_NONTERMINALS: &[BuiltinNonterminal] = &[
+    BuiltinNonterminal { name: "HOST", bash: "compgen -A hostname", fish: "__fish_print_hostnames", zsh: "_hosts", elvish: "getent hosts | each {|line| put (str:fields $line)[1..]}", powershell: "$env:COMPUTERNAME" },
+    BuiltinNonterminal { name: "USER", bash: "compgen -A user", fish: "__fish_complete_users", zsh: "_users", elvish: "getent passwd | cut -d: -f1", powershell: "Get-LocalUser | Select-Object -ExpandProperty Name" },
+    BuiltinNonterminal { name: "GROUP", bash: "compgen -A group", fish: "__fish_complete_groups", zsh: "_groups", elvish: "getent group | cut -d: -f1", powershell: "Get-LocalGroup | Select-Object -ExpandProperty Name" },
+    BuiltinNonterminal { name: "PID", bash: r#"compgen -W "$(ps -eo pid=)""#, fish: "__fish_complete_pids", zsh: "_pids", elvish: "ps -eo pid --no-headers", powershell: "Get-Process | Select-Object -ExpandProperty Id" },
+    BuiltinNonterminal { name: "SIGNAL", bash: "compgen -A signal", fish: "__fish_complete_signals", zsh: "_signals", elvish: "kill -l", powershell: "'HUP','INT','QUIT','KILL','TERM','USR1','USR2'" },
+    BuiltinNonterminal { name: "VARIABLE", bash: "compgen -A variable", fish: "__fish_complete_variables", zsh: "_parameters", elvish: "keys $E: | each {|k| put $k}", powershell: "Get-ChildItem Env: | Select-Object -ExpandProperty Name" },
+];
+
+
 pub fn get_match_final_state(dfa: &DFA, inputs: &[&str], completed_word_index: usize) -> Option<StateId> {
     let mut backtracking_stack = Vec::from_iter([(0, dfa.starting_state)]);
     while let Some((input_index, current_state)) = backtracking_stack.pop() {
@@ -108,7 +185,21 @@ pub fn get_match_final_state(dfa: &DFA, inputs: &[&str], completed_word_index: u
 
         for (transition_input, to) in dfa.transitions.get(&current_state).unwrap_or(&HashMap::default()) {
             if transition_input.matches_anything() {
-                backtracking_stack.push((input_index + 1, *to));
+                // A regex-typed nonterminal is the one `matches_anything()` transition whose
+                // already-typed word can actually be wrong: reject it here instead of
+                // unconditionally assuming anything satisfies it, same as a `Literal` below.
+                let allowed = match transition_input {
+                    Input::Any(MatchAnythingInput::Nonterminal(nonterm)) => {
+                        match as_regex_pattern(nonterm.as_str()) {
+                            Some(pattern) => regex_fully_matches(pattern, inputs[input_index]),
+                            None => true,
+                        }
+                    },
+                    _ => true,
+                };
+                if allowed {
+                    backtracking_stack.push((input_index + 1, *to));
+                }
             }
         }
 
@@ -124,11 +215,22 @@ pub fn get_match_final_state(dfa: &DFA, inputs: &[&str], completed_word_index: u
 }
 
 
-fn get_completions_for_input<'a, 'b>(input: &Input, prefix: &str, shell: Shell) -> Vec<(String, String)> {
+fn split_on_ifs<'a>(stdout: &'a str, ifs: &str) -> Vec<&'a str> {
+    if ifs == "\n" {
+        stdout.lines().collect()
+    }
+    else {
+        stdout.split(ifs).filter(|s| !s.is_empty()).collect()
+    }
+}
+
+
+fn get_completions_for_input<'a, 'b>(input: &Input, prefix: &str, shell: Shell, ifs: &str) -> Vec<Completion> {
     match input {
         Input::Literal(literal, description) => {
             if literal.starts_with(prefix) {
-                vec![(literal.as_str().to_string(), description.unwrap_or(ustr("")).as_str().to_string())]
+                let no_space = literal.ends_with('=');
+                vec![Completion::new(literal.as_str().to_string(), description.unwrap_or(ustr("")).as_str().to_string(), no_space)]
             }
             else {
                 vec![]
@@ -144,13 +246,13 @@ fn get_completions_for_input<'a, 'b>(input: &Input, prefix: &str, shell: Shell)
                 },
             };
 
-            let mut result: Vec<(String, String)> = stdout.lines().map(|line| match line.split_once("\t") {
-                Some((completion, description)) => (completion.to_owned(), description.to_owned()),
-                None => (line.to_string(), "".to_string()),
+            let mut result: Vec<Completion> = split_on_ifs(&stdout, ifs).into_iter().map(|line| match line.split_once("\t") {
+                Some((completion, description)) => Completion::new(completion.to_owned(), description.to_owned(), false),
+                None => Completion::new(line.to_string(), "".to_string(), false),
             }).collect();
 
             if !prefix.is_empty() {
-                result.retain(|(completion, _)| completion.starts_with(prefix));
+                result.retain(|completion| completion.value.starts_with(prefix));
             }
 
             result
@@ -165,7 +267,10 @@ fn get_completions_for_input<'a, 'b>(input: &Input, prefix: &str, shell: Shell)
                 },
             };
 
-            stdout.lines().into_iter().map(|line| (line.to_owned(), "".to_owned())).collect()
+            split_on_ifs(&stdout, ifs).into_iter().map(|line| {
+                let no_space = line.ends_with('/');
+                Completion::new(line.to_owned(), "".to_owned(), no_space)
+            }).collect()
         },
 
         Input::Any(MatchAnythingInput::Nonterminal(nonterm)) if nonterm.as_str() == "DIRECTORY" => {
@@ -177,15 +282,45 @@ fn get_completions_for_input<'a, 'b>(input: &Input, prefix: &str, shell: Shell)
                 },
             };
 
-            stdout.lines().into_iter().map(|line| (line.to_owned(), "".to_owned())).collect()
+            // A directory is always a valid prefix of a deeper path, so never insert the
+            // trailing space that would otherwise end the current argument.
+            split_on_ifs(&stdout, ifs).into_iter().map(|line| Completion::new(line.to_owned(), "".to_owned(), true)).collect()
+        },
+
+        Input::Any(MatchAnythingInput::Nonterminal(nonterm)) if as_regex_pattern(nonterm.as_str()).is_some() => {
+            // No enumerable candidate source backs a pure regex slot (a `{ command }`
+            // nonterminal's output is only filtered by prefix, never through a regex), so the
+            // one thing there is to offer is the prefix itself, once it's already a complete,
+            // valid value.
+            let pattern = as_regex_pattern(nonterm.as_str()).unwrap();
+            if !prefix.is_empty() && regex_fully_matches(pattern, prefix) {
+                vec![Completion::new(prefix.to_string(), "".to_string(), false)]
+            } else {
+                vec![]
+            }
         },
 
-        Input::Any(MatchAnythingInput::Nonterminal(_)) => vec![],
+        Input::Any(MatchAnythingInput::Nonterminal(nonterm)) => {
+            let stdout = match shell.complete_builtin_nonterminal(nonterm.as_str(), prefix) {
+                Some(Ok(stdout)) => stdout,
+                Some(Err(e)) => {
+                    eprintln!("{:?}", e);
+                    return vec![];
+                },
+                None => return vec![],
+            };
+
+            let mut result: Vec<Completion> = split_on_ifs(&stdout, ifs).into_iter().map(|line| Completion::new(line.to_owned(), "".to_owned(), false)).collect();
+            if !prefix.is_empty() {
+                result.retain(|completion| completion.value.starts_with(prefix));
+            }
+            result
+        },
     }
 }
 
 
-pub fn get_completions<'a, 'b>(dfa: &DFA, words_before_cursor: &'b [&'a str], completed_word_index: usize, shell: Shell) -> Vec<(String, String)> {
+pub fn get_completions<'a, 'b>(dfa: &DFA, words_before_cursor: &'b [&'a str], completed_word_index: usize, shell: Shell, ifs: &str) -> Vec<Completion> {
     let prefix = if completed_word_index < words_before_cursor.len() {
         words_before_cursor[completed_word_index]
     }
@@ -198,12 +333,115 @@ pub fn get_completions<'a, 'b>(dfa: &DFA, words_before_cursor: &'b [&'a str], co
         None => return vec![],
     };
 
-    let mut completions: Vec<(String, String)> = dfa.transitions.get(&state_id).unwrap_or(&HashMap::default()).iter().map(|(input, _)| get_completions_for_input(input, prefix, shell)).flatten().collect();
-    completions.sort_unstable();
+    let mut completions: Vec<Completion> = dfa.transitions.get(&state_id).unwrap_or(&HashMap::default()).iter().map(|(input, _)| get_completions_for_input(input, prefix, shell, ifs)).flatten().collect();
+    completions.sort_unstable_by(|a, b| a.value.cmp(&b.value));
     completions
 }
 
 
+/// Entry point for the `complgen complete` subcommand: loads a precompiled `dfa` and prints
+/// the candidates for `words[index]` to stdout, one per line, so a tiny per-shell stub can
+/// call back into the binary instead of sourcing a fully generated script. Each line carries
+/// a trailing `\x01` marker when `no_space` is set, so the calling shell stub knows not to
+/// insert a space after inserting that candidate. `words` is the whole command line as the
+/// shell sees it, command name first, and `index` is the cursor word's position in it (bash's
+/// `COMP_CWORD`), which is what every stub from `write_registration_stub` passes.
+pub fn run_complete_subcommand(dfa: &DFA, shell: Shell, index: usize, words: &[String], ifs: &str) -> anyhow::Result<()> {
+    // The DFA starts at the first argument, not at the command name, so drop `words[0]`.
+    let Some(index) = index.checked_sub(1) else { return Ok(()) };
+    let words: Vec<&str> = words.iter().skip(1).map(|w| w.as_str()).collect();
+    for completion in get_completions(dfa, &words, index, shell, ifs) {
+        if completion.no_space {
+            println!("{}\x01", completion.value);
+        }
+        else {
+            println!("{}", completion.value);
+        }
+    }
+    Ok(())
+}
+
+
+/// The shell snippet a user sources to register dynamic completion for `command`: it doesn't
+/// bake the DFA into the script at all, it just shells back out to `complgen complete` on
+/// every completion attempt so the candidates always reflect the live grammar/commands.
+///
+/// Each stub below strips the `\x01` `no_space` marker `run_complete_subcommand` appends (see
+/// its doc comment) before inserting a candidate, and applies whatever the shell's own
+/// mechanism for suppressing the trailing space is. bash only exposes that as a per-invocation
+/// `compopt -o nospace`, not a per-candidate flag, so it's only applied when it's unambiguous
+/// (a single candidate); fish and PowerShell have no equivalent hook for function-sourced
+/// candidates at all, so the marker is stripped there purely to stop it leaking into the
+/// inserted text.
+pub fn write_registration_stub<W: std::io::Write>(buffer: &mut W, shell: Shell, command: &str, dfa_path: &Path) -> anyhow::Result<()> {
+    let dfa_path = dfa_path.display();
+    match shell {
+        Shell::Bash => {
+            writeln!(buffer, r#"_{command}_complete () {{
+    local -a raw
+    raw=($(complgen complete --shell bash --index "$COMP_CWORD" "{dfa_path}" -- "${{COMP_WORDS[@]}}"))
+    COMPREPLY=()
+    local candidate
+    for candidate in "${{raw[@]}}"; do
+        COMPREPLY+=("${{candidate%$'\x01'}}")
+    done
+    if [[ ${{#COMPREPLY[@]}} -eq 1 && "${{raw[0]}}" == *$'\x01' ]]; then
+        compopt -o nospace
+    fi
+}}
+complete -F _{command}_complete {command}"#)?;
+        },
+        Shell::Fish => {
+            // fish has no per-candidate "no trailing space" hook for function-sourced
+            // completions, so the marker is only stripped here, not acted on.
+            writeln!(buffer, r#"complete -c {command} -f -a '(complgen complete --shell fish --index (count (commandline -opc)) "{dfa_path}" -- (commandline -opc) | string replace -r \x01$ "")'"#)?;
+        },
+        Shell::Zsh => {
+            writeln!(buffer, r#"#compdef {command}
+_{command}_complete () {{
+    local -a raw completions nospace_completions
+    raw=("${{(@f)$(complgen complete --shell zsh --index "$((CURRENT-1))" "{dfa_path}" -- "${{words[@]}}")}}")
+    local line
+    for line in "${{raw[@]}}"; do
+        if [[ "$line" == *$'\x01' ]]; then
+            nospace_completions+=("${{line%$'\x01'}}")
+        else
+            completions+=("$line")
+        fi
+    done
+    compadd -a completions
+    compadd -S '' -a nospace_completions
+}}
+compdef _{command}_complete {command}"#)?;
+        },
+        Shell::Elvish => {
+            writeln!(buffer, r#"set edit:completion:arg-completer[{command}] = {{|@words|
+    var index = (- (count $words) 1)
+    for line [(complgen complete --shell elvish --index $index "{dfa_path}" -- $@words)] {{
+        if (str:has-suffix $line "\x01") {{
+            edit:complex-candidate (str:trim-suffix $line "\x01") &code-suffix=''
+        }} else {{
+            put $line
+        }}
+    }}
+}}"#)?;
+        },
+        Shell::Powershell => {
+            // PowerShell's CompletionResult has no "no trailing space" flag either; strip the
+            // marker so it doesn't end up in the inserted text.
+            writeln!(buffer, r#"Register-ArgumentCompleter -Native -CommandName {command} -ScriptBlock {{
+    param($WordToComplete, $CommandAst, $CursorPosition)
+    $CommandWords = $CommandAst.CommandElements | ForEach-Object {{ $_.ToString() }}
+    complgen complete --shell powershell --index ($CommandWords.Count - 1) "{dfa_path}" -- @CommandWords |
+        ForEach-Object {{ $_.TrimEnd([char]1) }} |
+        ForEach-Object {{ [System.Management.Automation.CompletionResult]::new($_, $_, 'ParameterValue', $_) }}
+}}"#)?;
+        },
+    }
+    Ok(())
+}
+
+
 #[cfg(test)]
 mod tests {
     use bumpalo::Bump;
@@ -213,23 +451,23 @@ mod tests {
 
     use super::*;
 
-    fn get_grammar_completions<'a, 'b>(grammar: &str, words_before_cursor: &'b [&'a str], completed_word_index: usize) -> Vec<(String, String)> {
+    fn get_grammar_completions<'a, 'b>(grammar: &str, words_before_cursor: &'b [&'a str], completed_word_index: usize) -> Vec<Completion> {
         let g = Grammar::parse(grammar).unwrap();
         let validated = ValidGrammar::from_grammar(g).unwrap();
         let arena = Bump::new();
         let regex = AugmentedRegex::from_expr(&validated.expr, &arena);
         let dfa = DFA::from_regex(&regex);
         let dfa = dfa.minimize();
-        get_completions(&dfa, words_before_cursor, completed_word_index, Shell::Bash)
+        get_completions(&dfa, words_before_cursor, completed_word_index, Shell::Bash, "\n")
     }
 
     #[test]
     fn completes_darcs_add() {
         const GRAMMAR: &str = r#"darcs add ( --boring | ( --case-ok | --reserved-ok ) | ( ( -r | --recursive ) | --not-recursive ) | ( --date-trick | --no-date-trick ) | --repodir <DIRECTORY> | --dry-run | --umask <UMASK> | ( --debug | --debug-verbose | --debug-http | ( -v | --verbose ) | ( -q | --quiet ) | --standard-verbosity ) | --timings | ( --posthook <COMMAND> | --no-posthook ) | ( --prompt-posthook | --run-posthook ) | ( --prehook <COMMAND> | --no-prehook ) | ( --prompt-prehook | --run-prehook ) ) ... ( <FILE> | <DIRECTORY> )...;"#;
-        assert_eq!(get_grammar_completions(GRAMMAR, &[], 0), vec![("add".to_string(), "".to_string())]);
+        assert_eq!(get_grammar_completions(GRAMMAR, &[], 0), vec![Completion::new("add".to_string(), "".to_string(), false)]);
 
         let input = vec!["add"];
-        let generated: HashSet<_> = HashSet::from_iter(get_grammar_completions(GRAMMAR, &input, 1).into_iter().map(|(completion, _)| completion));
+        let generated: HashSet<_> = HashSet::from_iter(get_grammar_completions(GRAMMAR, &input, 1).into_iter().map(|completion| completion.value));
         let expected = HashSet::from_iter(["--boring", "--debug", "--dry-run", "--no-prehook", "--prehook", "--quiet", "--reserved-ok", "--standard-verbosity", "--verbose", "-v", "--case-ok", "--debug-http", "--no-date-trick", "--not-recursive", "--prompt-posthook", "--recursive", "--run-posthook", "--timings", "-q", "--date-trick", "--debug-verbose", "--no-posthook", "--posthook", "--prompt-prehook", "--repodir", "--run-prehook", "--umask", "-r"].map(|s| s.to_string()));
         assert_eq!(generated, expected);
     }
@@ -250,7 +488,7 @@ grep [<OPTION>]...;
 <WHEN> ::= always | never | auto;
 "#;
         let input = vec!["--color"];
-        let generated: HashSet<_> = HashSet::from_iter(get_grammar_completions(GRAMMAR, &input, 1).into_iter().map(|(completion, _)| completion));
+        let generated: HashSet<_> = HashSet::from_iter(get_grammar_completions(GRAMMAR, &input, 1).into_iter().map(|completion| completion.value));
         let expected = HashSet::from_iter(["always", "auto", "never", "--extended-regexp", "--color"].map(|s| s.to_string()));
         assert_eq!(generated, expected);
     }
@@ -262,7 +500,7 @@ cargo [<toolchain>] (--version | --help);
 <toolchain> ::= { rustup toolchain list | cut -d' ' -f1 | sed 's/^/+/' };
 "#;
         let input = vec!["foo"];
-        let generated: HashSet<_> = HashSet::from_iter(get_grammar_completions(GRAMMAR, &input, 1).into_iter().map(|(completion, _)| completion));
+        let generated: HashSet<_> = HashSet::from_iter(get_grammar_completions(GRAMMAR, &input, 1).into_iter().map(|completion| completion.value));
         let expected = HashSet::from_iter(["--version", "--help"].map(|s| s.to_string()));
         assert_eq!(generated, expected);
     }
@@ -273,7 +511,7 @@ cargo [<toolchain>] (--version | --help);
 grep (--context "print NUM lines of output context" <NUM> | --version | --help)...;
 "#;
         let input = vec!["--context", "123"];
-        let generated: HashSet<_> = HashSet::from_iter(get_grammar_completions(GRAMMAR, &input, 2).into_iter().map(|(completion, _)| completion));
+        let generated: HashSet<_> = HashSet::from_iter(get_grammar_completions(GRAMMAR, &input, 2).into_iter().map(|completion| completion.value));
         let expected = HashSet::from_iter(["--version", "--help", "--context"].map(|s| s.to_string()));
         assert_eq!(generated, expected);
     }
@@ -284,8 +522,16 @@ grep (--context "print NUM lines of output context" <NUM> | --version | --help).
 grep (--help | --version);
 "#;
         let input = vec!["--h"];
-        let generated: HashSet<_> = HashSet::from_iter(get_grammar_completions(GRAMMAR, &input, 0).into_iter().map(|(completion, _)| completion));
+        let generated: HashSet<_> = HashSet::from_iter(get_grammar_completions(GRAMMAR, &input, 0).into_iter().map(|completion| completion.value));
         let expected = HashSet::from_iter(["--help"].map(|s| s.to_string()));
         assert_eq!(generated, expected);
     }
+
+    #[test]
+    fn directory_completions_suppress_trailing_space() {
+        const GRAMMAR: &str = r#"find --repodir <DIRECTORY>;"#;
+        let input = vec!["--repodir", ""];
+        let generated = get_grammar_completions(GRAMMAR, &input, 1);
+        assert!(generated.iter().all(|completion| completion.no_space));
+    }
 }
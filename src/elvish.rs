@@ -0,0 +1,114 @@
+use std::fmt::Write;
+
+use complgen::StateId;
+
+use crate::error::Result;
+use crate::dfa::DFA;
+use crate::regex::{Input, MatchAnythingInput};
+use crate::codegen_common::{as_regex_pattern, collect_reachable_states};
+
+
+fn write_dfa_state<W: Write>(buffer: &mut W, dfa: &DFA, state: StateId) -> Result<()> {
+    write!(buffer, r#"
+fn state-{state} {{|words word-index|
+    # $words is 0-based and its last element is the (possibly empty) word under the cursor,
+    # so word-index has reached it once it's one less than $words' length, not equal to it.
+    if (== $word-index (- (count $words) 1)) {{
+"#, state = state)?;
+
+    for (input, _) in dfa.get_transitions_from(state) {
+        match input {
+            // A literal ending in '=' (e.g. a `--foo=`-shaped option) shouldn't get Elvish's
+            // default trailing space inserted after it.
+            Input::Literal(literal, _) if literal.ends_with('=') => {
+                write!(buffer, r#"        edit:complex-candidate {literal} &code-suffix=''
+"#, literal = literal)?;
+            },
+            Input::Literal(literal, _) => {
+                write!(buffer, r#"        put {literal}
+"#, literal = literal)?;
+            },
+            Input::Any(MatchAnythingInput::Nonterminal(nonterm)) if nonterm.as_str() == "PATH" => {
+                write!(buffer, r#"        put (edit:complete-filename $words[-1])
+"#)?;
+            },
+            Input::Any(MatchAnythingInput::Nonterminal(nonterm)) if nonterm.as_str() == "DIRECTORY" => {
+                write!(buffer, r#"        put (edit:complete-filename $words[-1] | each {{|c| if (str:has-suffix $c /) {{ put $c }} }})
+"#)?;
+            },
+            Input::Any(MatchAnythingInput::Command(command)) => {
+                write!(buffer, r#"        {command}
+"#, command = command)?;
+            },
+            // A regex-typed nonterminal has no enumerable candidate source (see complete.rs's
+            // get_completions_for_input), so it's excluded here and falls through to the plain
+            // "nothing to offer" arm below, same as one with no registered builtin.
+            Input::Any(MatchAnythingInput::Nonterminal(nonterm)) if as_regex_pattern(nonterm.as_str()).is_none() => {
+                if let Some(builtin) = crate::complete::BUILTIN_NONTERMINALS.iter().find(|builtin| builtin.name == nonterm.as_str()) {
+                    write!(buffer, r#"        {elvish}
+"#, elvish = builtin.elvish)?;
+                }
+            },
+            Input::Any(MatchAnythingInput::Nonterminal(_)) => {},
+        }
+    }
+
+    write!(buffer, r#"        return
+    }}
+
+    var word = $words[$word-index]
+"#)?;
+
+    let mut regex_destination: Option<(String, StateId)> = None;
+    let mut any_destination: Option<StateId> = None;
+    for (input, to) in dfa.get_transitions_from(state) {
+        match input {
+            Input::Literal(literal, _) => {
+                write!(buffer, r#"    if (== $word {literal}) {{
+        state-{to} $words (+ $word-index 1)
+        return
+    }}
+"#, literal = literal, to = to)?;
+            },
+            Input::Any(MatchAnythingInput::Nonterminal(nonterm)) if as_regex_pattern(nonterm.as_str()).is_some() => {
+                let pattern = as_regex_pattern(nonterm.as_str()).unwrap().to_string();
+                regex_destination.get_or_insert((pattern, to));
+            },
+            Input::Any(_) => {
+                any_destination.get_or_insert(to);
+            },
+        }
+    }
+
+    if let Some((pattern, to)) = &regex_destination {
+        write!(buffer, r#"    if (re:match '^({pattern})$' $word) {{
+        state-{to} $words (+ $word-index 1)
+        return
+    }}
+"#, pattern = pattern, to = to)?;
+    }
+
+    if let Some(to) = any_destination {
+        write!(buffer, r#"    state-{to} $words (+ $word-index 1)
+"#, to = to)?;
+    }
+
+    write!(buffer, r#"}}
+"#)?;
+    Ok(())
+}
+
+
+pub fn write_completion_script<W: Write>(buffer: &mut W, command: &str, dfa: &DFA) -> Result<()> {
+    for state in collect_reachable_states(dfa) {
+        write_dfa_state(buffer, dfa, state)?;
+    }
+
+    write!(buffer, r#"
+set edit:completion:arg-completer[{command}] = {{|@words|
+    state-{starting_state} $words 1
+}}
+"#, command = command, starting_state = dfa.starting_state)?;
+
+    Ok(())
+}
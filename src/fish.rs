@@ -0,0 +1,128 @@
+use std::fmt::Write;
+
+use complgen::StateId;
+
+use crate::error::Result;
+use crate::dfa::DFA;
+use crate::regex::{Input, MatchAnythingInput};
+use crate::codegen_common::{as_regex_pattern, collect_reachable_states};
+
+
+// Fish reads candidates from a completion function as `value<TAB>description` lines, which is
+// what populates the description column in the completion pager -- the functional equivalent
+// of passing `-d` on a static `complete` entry.
+fn write_dfa_state<W: Write>(buffer: &mut W, dfa: &DFA, state: StateId) -> Result<()> {
+    write!(buffer, r#"
+function __{state}_candidates
+"#, state = state)?;
+
+    for (input, _) in dfa.get_transitions_from(state) {
+        match input {
+            // fish has no per-candidate "no trailing space" hook for completions sourced from
+            // a function like this one, so a literal ending in '=' doesn't get special handling
+            // here the way it does in bash.rs/zsh.rs/elvish.rs.
+            Input::Literal(literal, description) => {
+                let description = description.unwrap_or_default();
+                write!(buffer, r#"    printf '%s\t%s\n' {literal} "{description}"
+"#, literal = literal, description = description)?;
+            },
+            Input::Any(MatchAnythingInput::Nonterminal(nonterm)) if nonterm.as_str() == "PATH" => {
+                write!(buffer, r#"    __fish_complete_path
+"#)?;
+            },
+            Input::Any(MatchAnythingInput::Nonterminal(nonterm)) if nonterm.as_str() == "DIRECTORY" => {
+                write!(buffer, r#"    __fish_complete_directories
+"#)?;
+            },
+            Input::Any(MatchAnythingInput::Command(command)) => {
+                write!(buffer, r#"    {command}
+"#, command = command)?;
+            },
+            // A regex-typed nonterminal has no enumerable candidate source (see complete.rs's
+            // get_completions_for_input), so it's excluded here and falls through to the plain
+            // "nothing to offer" arm below, same as one with no registered builtin.
+            Input::Any(MatchAnythingInput::Nonterminal(nonterm)) if as_regex_pattern(nonterm.as_str()).is_none() => {
+                if let Some(builtin) = crate::complete::BUILTIN_NONTERMINALS.iter().find(|builtin| builtin.name == nonterm.as_str()) {
+                    write!(buffer, r#"    {fish}
+"#, fish = builtin.fish)?;
+                }
+            },
+            Input::Any(MatchAnythingInput::Nonterminal(_)) => {},
+        }
+    }
+
+    write!(buffer, r#"end
+
+function __state_{state}
+    # Unlike zsh's $words, (commandline -opc) never includes a placeholder for the
+    # in-progress word at the cursor, so "nothing left to consume" is index > count,
+    # not index == count.
+    set -l words (commandline -opc)
+    if test $__complgen_word_index -gt (count $words)
+        __{state}_candidates
+        return
+    end
+
+    set -l word $words[$__complgen_word_index]
+"#, state = state)?;
+
+    let mut regex_destination: Option<(String, StateId)> = None;
+    let mut any_destination: Option<StateId> = None;
+    for (input, to) in dfa.get_transitions_from(state) {
+        match input {
+            Input::Literal(literal, _) => {
+                write!(buffer, r#"    if test "$word" = {literal}
+        set __complgen_word_index (math $__complgen_word_index + 1)
+        __state_{to}
+        return
+    end
+"#, literal = literal, to = to)?;
+            },
+            Input::Any(MatchAnythingInput::Nonterminal(nonterm)) if as_regex_pattern(nonterm.as_str()).is_some() => {
+                let pattern = as_regex_pattern(nonterm.as_str()).unwrap().to_string();
+                regex_destination.get_or_insert((pattern, to));
+            },
+            Input::Any(_) => {
+                any_destination.get_or_insert(to);
+            },
+        }
+    }
+
+    if let Some((pattern, to)) = &regex_destination {
+        write!(buffer, r#"    if string match -rq -- '^({pattern})$' "$word"
+        set __complgen_word_index (math $__complgen_word_index + 1)
+        __state_{to}
+        return
+    end
+"#, pattern = pattern, to = to)?;
+    }
+
+    if let Some(to) = any_destination {
+        write!(buffer, r#"    set __complgen_word_index (math $__complgen_word_index + 1)
+    __state_{to}
+"#, to = to)?;
+    }
+
+    write!(buffer, r#"end
+"#)?;
+    Ok(())
+}
+
+
+pub fn write_completion_script<W: Write>(buffer: &mut W, command: &str, dfa: &DFA) -> Result<()> {
+    for state in collect_reachable_states(dfa) {
+        write_dfa_state(buffer, dfa, state)?;
+    }
+
+    write!(buffer, r#"
+function __{command}_complete
+    # (commandline -opc) is 1-based and its first word is the command itself, so start past it.
+    set -g __complgen_word_index 2
+    __state_{starting_state}
+end
+
+complete -c {command} -f -a '(__{command}_complete)'
+"#, command = command, starting_state = dfa.starting_state)?;
+
+    Ok(())
+}
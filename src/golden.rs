@@ -0,0 +1,158 @@
+// Golden-file completion test harness.
+//
+// Each subdirectory of `tests/golden/` is one fixture:
+//   grammar.usage  -- a complgen grammar
+//   input.txt      -- two lines: the space-separated words typed so far (possibly empty), and
+//                     the index of the word currently being completed, for a grammar that parses
+//   expected.out   -- the resulting completions, one per line, sorted
+//   expected.err   -- a substring expected in the parse error's `{:?}` rendering, for a
+//                     deliberately malformed grammar (no `input.txt` needed in that case)
+//
+// Set `COMPLGEN_ACCEPT=1` to rewrite `expected.out` files from the actual output instead of
+// failing on a mismatch, mirroring Mercurial's `run-tests.py --accept`.
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::path::{Path, PathBuf};
+
+    use bumpalo::Bump;
+
+    use crate::complete::{self, Completion, Shell};
+    use crate::dfa::DFA;
+    use crate::grammar::{Grammar, ValidGrammar};
+    use crate::regex::AugmentedRegex;
+    use crate::{bash, elvish, fish, powershell, zsh};
+
+    fn fixtures_dir() -> PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR")).join("tests").join("golden")
+    }
+
+    fn discover_fixtures() -> Vec<PathBuf> {
+        let mut dirs: Vec<PathBuf> = fs::read_dir(fixtures_dir())
+            .expect("tests/golden/ must exist")
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.path())
+            .filter(|path| path.is_dir())
+            .collect();
+        dirs.sort();
+        dirs
+    }
+
+    fn parse_input(dir: &Path) -> (Vec<String>, usize) {
+        let contents = fs::read_to_string(dir.join("input.txt")).unwrap_or_default();
+        let mut lines = contents.lines();
+        let words = lines.next().unwrap_or("").split_whitespace().map(str::to_owned).collect();
+        let completed_word_index = lines.next().unwrap_or("0").trim().parse().unwrap_or(0);
+        (words, completed_word_index)
+    }
+
+    // Every literal reachable from the DFA's starting state, i.e. the candidates offered for
+    // the very first word -- used below to sanity-check that each static generator's emitted
+    // script text actually embeds the grammar's own literals, rather than nothing at all.
+    fn starting_literals(dfa: &DFA) -> Vec<String> {
+        dfa.get_transitions_from(dfa.starting_state)
+            .into_iter()
+            .filter_map(|(input, _)| match input {
+                crate::regex::Input::Literal(literal, _) => Some(literal.to_string()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    // Checks every static per-shell generator's emitted script against the same DFA: each one
+    // must at least mention every literal reachable from the starting state, so a generator that
+    // silently emits an empty or truncated script would fail here. This doesn't shell-evaluate
+    // the generated scripts (none of bash/zsh/fish/elvish/powershell are assumed installed), so
+    // it can't catch a runtime-only bug like an off-by-one word index; it only proves codegen
+    // actually ran and touched the grammar for every shell, which the in-process comparison
+    // below -- varying only the `Shell` enum passed to `get_completions` -- does not.
+    fn assert_generators_embed_literals(dfa: &DFA, literals: &[String]) {
+        let command = "dummy";
+        let generators: &[(&str, fn(&mut String, &str, &DFA) -> crate::error::Result<()>)] = &[
+            ("bash", bash::write_completion_script),
+            ("zsh", zsh::write_completion_script),
+            ("fish", fish::write_completion_script),
+            ("elvish", elvish::write_completion_script),
+            ("powershell", powershell::write_completion_script),
+        ];
+        for (name, write_completion_script) in generators {
+            let mut script = String::new();
+            write_completion_script(&mut script, command, dfa).unwrap();
+            for literal in literals {
+                assert!(script.contains(literal.as_str()), "{name} generator's script is missing literal {literal:?}");
+            }
+        }
+    }
+
+    // Drives the in-process parse -> codegen -> completion pipeline, varying only the `Shell`
+    // enum passed to `get_completions`. Literal and regex-slot fixtures reach `get_completions_for_input`
+    // arms that don't shell out (the regex case only ever echoes back an already-valid prefix), so
+    // every shell is expected to agree on one `expected.out` there; PATH/DIRECTORY/builtin-nonterminal
+    // completions shell out to a real bash/fish/zsh/elvish/pwsh binary per shell and aren't covered
+    // here; formatting differences between those binaries' own completion output would make a single
+    // shared `expected.out` across all five shells the wrong fixture shape for them. Agreement on the
+    // cases this harness does cover is itself useful signal (it exercises `get_completions_for_input`'s
+    // per-shell dispatch), but it is NOT evidence that any generated shell script was ever run -- see
+    // `assert_generators_embed_literals` for the part of this harness that actually touches the generators.
+    fn run_fixture(dir: &Path) -> Result<Vec<String>, String> {
+        let grammar_source = fs::read_to_string(dir.join("grammar.usage")).unwrap();
+        let g = Grammar::parse(&grammar_source).map_err(|e| format!("{:?}", e))?;
+        let validated = ValidGrammar::from_grammar(g).map_err(|e| format!("{:?}", e))?;
+        let arena = Bump::new();
+        let regex = AugmentedRegex::from_expr(&validated.expr, &arena);
+        let dfa = DFA::from_regex(&regex).minimize();
+
+        assert_generators_embed_literals(&dfa, &starting_literals(&dfa));
+
+        let (words, completed_word_index) = parse_input(dir);
+        let words: Vec<&str> = words.iter().map(String::as_str).collect();
+
+        let mut values: Option<Vec<String>> = None;
+        for shell in [Shell::Bash, Shell::Zsh, Shell::Fish, Shell::Elvish, Shell::Powershell] {
+            let mut completions: Vec<String> = complete::get_completions(&dfa, &words, completed_word_index, shell, "\n")
+                .into_iter()
+                .map(|completion: Completion| completion.value)
+                .collect();
+            completions.sort();
+
+            match &values {
+                Some(expected) => assert_eq!(*expected, completions, "{:?}: {shell:?} disagreed with {:?}", dir, Shell::Bash),
+                None => values = Some(completions),
+            }
+        }
+        Ok(values.unwrap_or_default())
+    }
+
+    #[test]
+    fn golden_fixtures_match() {
+        let accept = std::env::var("COMPLGEN_ACCEPT").is_ok();
+        for dir in discover_fixtures() {
+            let expected_err_path = dir.join("expected.err");
+            let expected_out_path = dir.join("expected.out");
+
+            match run_fixture(&dir) {
+                Ok(actual) => {
+                    assert!(!expected_err_path.exists(), "{:?}: grammar parsed but expected.err says it shouldn't", dir);
+
+                    if accept {
+                        fs::write(&expected_out_path, format!("{}\n", actual.join("\n"))).unwrap();
+                        continue;
+                    }
+
+                    let expected: Vec<String> = fs::read_to_string(&expected_out_path)
+                        .unwrap_or_default()
+                        .lines()
+                        .map(str::to_owned)
+                        .collect();
+                    assert_eq!(actual, expected, "{:?}: completions did not match expected.out", dir);
+                },
+                Err(message) => {
+                    let expected_err = fs::read_to_string(&expected_err_path)
+                        .unwrap_or_else(|_| panic!("{:?}: grammar failed to parse ({message}) but has no expected.err", dir));
+                    assert!(message.contains(expected_err.trim()), "{:?}: error {message:?} did not contain {expected_err:?}", dir);
+                },
+            }
+        }
+    }
+}
@@ -1,11 +1,15 @@
 use std::rc::Rc;
 
+use std::collections::HashSet;
+
 use nom::{
     branch::alt,
     bytes::complete::{is_not, tag, take_while1, escaped},
-    character::{complete::{char, multispace0, multispace1, one_of}, is_alphanumeric},
+    character::{complete::{char, multispace0, multispace1, one_of}, is_alphanumeric, is_alphabetic},
+    combinator::{fail, opt},
     multi::many0,
-    IResult, combinator::fail, error::context,
+    sequence::delimited,
+    IResult, error::context,
 };
 
 use complgen::{Error, Result};
@@ -14,8 +18,14 @@ use ustr::{Ustr, ustr, UstrMap};
 // Can't use an arena here until proptest supports non-owned types: https://github.com/proptest-rs/proptest/issues/9
 #[derive(Clone, PartialEq)]
 pub enum Expr {
-    Literal(Ustr), // e.g. an option: "--help", or a command: "build"
+    Literal(Ustr, Option<Ustr>), // e.g. an option: "--help", or a command: "build", with an optional description
     Variable(Ustr), // e.g. <FILE>, <PATH>, <DIR>, etc.
+    // A nonterminal defined as `<NAME> ::= /pattern/;` instead of an expansion: the pattern
+    // source, as written. Consumers match it against a whole word, anchored at both ends; a
+    // partially typed word is not matched against it as a prefix. Stored uncompiled, same as `Variable` stores a bare
+    // name rather than its resolved expansion, since compiling it is only needed where it's
+    // actually matched against candidates.
+    Regex(Ustr),
     Sequence(Vec<Rc<Expr>>),
     Alternative(Vec<Rc<Expr>>),
     Optional(Rc<Expr>),
@@ -25,8 +35,12 @@ pub enum Expr {
 impl std::fmt::Debug for Expr {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
-            Self::Literal(arg0) => f.write_fmt(format_args!(r#"Rc::new(Literal(ustr("{}")))"#, arg0)),
+            Self::Literal(arg0, arg1) => match arg1 {
+                Some(descr) => f.write_fmt(format_args!(r#"Rc::new(Literal(ustr("{}"), Some(ustr("{}"))))"#, arg0, descr)),
+                None => f.write_fmt(format_args!(r#"Rc::new(Literal(ustr("{}"), None))"#, arg0)),
+            },
             Self::Variable(arg0) => f.write_fmt(format_args!(r#"Rc::new(Variable(ustr("{}")))"#, arg0)),
+            Self::Regex(arg0) => f.write_fmt(format_args!(r#"Rc::new(Regex(ustr("{}")))"#, arg0)),
             Self::Sequence(arg0) => f.write_fmt(format_args!(r#"Rc::new(Sequence(vec!{:?}))"#, arg0)),
             Self::Alternative(arg0) => f.write_fmt(format_args!(r#"Rc::new(Alternative(vec!{:?}))"#, arg0)),
             Self::Optional(arg0) => f.write_fmt(format_args!(r#"Rc::new(Optional({:?}))"#, arg0)),
@@ -46,15 +60,50 @@ fn terminal(input: &str) -> IResult<&str, &str> {
     Ok((input, term))
 }
 
+// A terminal may be followed by a quoted description, e.g. `--summary "show a summary of
+// changes"`, which is surfaced as the candidate's description in shells that support one
+// (zsh, fish) and ignored elsewhere.
+fn terminal_description(input: &str) -> IResult<&str, &str> {
+    let (input, _) = multispace1(input)?;
+    delimited(char('"'), is_not("\""), char('"'))(input)
+}
+
 fn terminal_expr(input: &str) -> IResult<&str, Expr> {
     let (input, literal) = context("terminal", terminal)(input)?;
-    Ok((input, Expr::Literal(ustr(literal))))
+    let (input, description) = opt(terminal_description)(input)?;
+    Ok((input, Expr::Literal(ustr(literal), description.map(ustr))))
+}
+
+// `<PORT> ::= /[0-9]{1,5}/;` -- lets a grammar author constrain a free-form argument (ports,
+// hashes, darcs' `--umask <UMASK>`) to a regular expression instead of enumerating candidates
+// or offering nothing. Delimited the same way a quoted terminal description is, but with `/`
+// instead of `"`, and validated eagerly here so a malformed pattern is a parse error rather
+// than a surprise at completion time.
+fn regex_literal(input: &str) -> IResult<&str, &str> {
+    let (input, pattern) = delimited(char('/'), is_not("/"), char('/'))(input)?;
+    if regex::Regex::new(pattern).is_err() {
+        return fail(input);
+    }
+    Ok((input, pattern))
+}
+
+fn regex_expr(input: &str) -> IResult<&str, Expr> {
+    let (input, pattern) = context("regex", regex_literal)(input)?;
+    Ok((input, Expr::Regex(ustr(pattern))))
 }
 
 fn symbol(input: &str) -> IResult<&str, &str> {
     let (input, _) = char('<')(input)?;
     let (input, name) = is_not(">")(input)?;
     let (input, _) = char('>')(input)?;
+    // `is_not(">")` above would otherwise happily accept `<regex:...>` as an ordinary nonterminal
+    // name, which downstream (complete.rs and every per-shell generator) is the exact sentinel
+    // prefix a `<NAME> ::= /pattern/;` regex definition is compiled down to. Reject it here, at
+    // parse time, the same way a malformed regex pattern is rejected in `regex_literal`, so a
+    // grammar author's own `<regex:...>` can never be silently reinterpreted as one.
+    if name.starts_with("regex:") {
+        return fail(input);
+    }
     Ok((input, name))
 }
 
@@ -92,6 +141,7 @@ fn expr_no_alternative_no_sequence(input: &str) -> IResult<&str, Expr> {
         symbol_expr,
         optional_expr,
         parenthesized_expr,
+        regex_expr,
         terminal_expr,
     ))(input)?;
 
@@ -227,7 +277,8 @@ pub struct Validated {
 
 fn resolve_variables(expr: Rc<Expr>, vars: &UstrMap<Rc<Expr>>) -> Rc<Expr> {
     match expr.as_ref() {
-        Expr::Literal(_) => Rc::clone(&expr),
+        Expr::Literal(_, _) => Rc::clone(&expr),
+        Expr::Regex(_) => Rc::clone(&expr),
         Expr::Variable(name) => {
             match vars.get(&name) {
                 Some(replacement) => {
@@ -292,8 +343,64 @@ fn resolve_variables(expr: Rc<Expr>, vars: &UstrMap<Rc<Expr>>) -> Rc<Expr> {
 }
 
 
+fn collect_variable_refs(expr: &Expr, refs: &mut HashSet<Ustr>) {
+    match expr {
+        Expr::Literal(_, _) | Expr::Regex(_) => {},
+        Expr::Variable(name) => { refs.insert(*name); },
+        Expr::Sequence(children) | Expr::Alternative(children) => {
+            for child in children {
+                collect_variable_refs(child, refs);
+            }
+        },
+        Expr::Optional(child) | Expr::Many1(child) => collect_variable_refs(child, refs),
+    }
+}
+
+// Kahn's algorithm over the "variable mentions variable" dependency graph, so that e.g.
+// `<FOO> ::= <BAR>;` has `<BAR>` resolved (its own `Variable` references replaced by their
+// definitions) before it's substituted into `<FOO>`. A variable that depends on itself,
+// directly or transitively, can never be fully resolved and is rejected.
 fn get_topologically_ordered_variables(variable_definitions: &UstrMap<Rc<Expr>>) -> Result<Vec<Ustr>> {
-    todo!();
+    let mut in_degree: UstrMap<usize> = variable_definitions.keys().map(|name| (*name, 0)).collect();
+    let mut dependents: UstrMap<Vec<Ustr>> = Default::default();
+
+    for (name, rhs) in variable_definitions {
+        let mut refs: HashSet<Ustr> = Default::default();
+        collect_variable_refs(rhs, &mut refs);
+        for referenced in refs {
+            if !variable_definitions.contains_key(&referenced) {
+                continue;
+            }
+            *in_degree.get_mut(name).unwrap() += 1;
+            dependents.entry(referenced).or_default().push(*name);
+        }
+    }
+
+    let mut ready: Vec<Ustr> = in_degree.iter().filter(|(_, degree)| **degree == 0).map(|(name, _)| *name).collect();
+    ready.sort_unstable();
+
+    let mut order: Vec<Ustr> = Default::default();
+    while let Some(name) = ready.pop() {
+        order.push(name);
+        if let Some(waiting) = dependents.get(&name) {
+            let mut unblocked: Vec<Ustr> = Default::default();
+            for dependent in waiting {
+                let degree = in_degree.get_mut(dependent).unwrap();
+                *degree -= 1;
+                if *degree == 0 {
+                    unblocked.push(*dependent);
+                }
+            }
+            ready.extend(unblocked);
+        }
+        ready.sort_unstable();
+    }
+
+    if order.len() != variable_definitions.len() {
+        return Err(Error::ParsingError("circular variable definition".to_string()));
+    }
+
+    Ok(order)
 }
 
 
@@ -379,6 +486,272 @@ pub fn parse(input: &str) -> Result<Grammar> {
 }
 
 
+// --- Standard (PEG-flavored) EBNF front-end ---
+//
+// Lowers `A ::= B C | D;`-style grammars, the notation used by e.g. PEG.js and Factor's
+// `peg.ebnf`, into the same `Grammar`/`Statement`/`Expr` AST the native complgen DSL produces,
+// so users can import existing EBNF specs instead of hand-rewriting them. The two notations
+// disagree on what a bare word means: here a bare identifier is always a nonterminal reference
+// (`Expr::Variable`), and only a quoted string is a terminal (`Expr::Literal`).
+
+fn ebnf_identifier(input: &str) -> IResult<&str, &str> {
+    fn is_identifier_char(c: char) -> bool {
+        c.is_ascii() && (is_alphanumeric(c as u8) || c == '_' || c == '-')
+    }
+    nom::combinator::recognize(nom::sequence::pair(
+        nom::character::complete::satisfy(|c| c.is_ascii() && is_alphabetic(c as u8)),
+        nom::bytes::complete::take_while(is_identifier_char),
+    ))(input)
+}
+
+
+fn ebnf_quoted_terminal(input: &str) -> IResult<&str, &str> {
+    alt((
+        delimited(char('"'), is_not("\""), char('"')),
+        delimited(char('\''), is_not("'"), char('\'')),
+    ))(input)
+}
+
+
+fn ebnf_terminal_expr(input: &str) -> IResult<&str, Expr> {
+    let (input, literal) = context("ebnf terminal", ebnf_quoted_terminal)(input)?;
+    Ok((input, Expr::Literal(ustr(literal), None)))
+}
+
+
+fn ebnf_variable_expr(input: &str) -> IResult<&str, Expr> {
+    let (input, name) = context("ebnf identifier", ebnf_identifier)(input)?;
+    Ok((input, Expr::Variable(ustr(name))))
+}
+
+
+fn ebnf_parenthesized_expr(input: &str) -> IResult<&str, Expr> {
+    let (input, _) = char('(')(input)?;
+    let (input, _) = multispace0(input)?;
+    let (input, e) = ebnf_alternative_expr(input)?;
+    let (input, _) = multispace0(input)?;
+    let (input, _) = char(')')(input)?;
+    Ok((input, e))
+}
+
+
+fn ebnf_factor_no_postfix(input: &str) -> IResult<&str, Expr> {
+    alt((
+        ebnf_parenthesized_expr,
+        ebnf_terminal_expr,
+        ebnf_variable_expr,
+    ))(input)
+}
+
+
+fn ebnf_factor(input: &str) -> IResult<&str, Expr> {
+    let (input, e) = ebnf_factor_no_postfix(input)?;
+    let (input, postfix) = opt(one_of("+?*"))(input)?;
+    let e = match postfix {
+        Some('+') => Expr::Many1(Rc::new(e)),
+        Some('?') => Expr::Optional(Rc::new(e)),
+        Some('*') => Expr::Optional(Rc::new(Expr::Many1(Rc::new(e)))),
+        _ => e,
+    };
+    Ok((input, e))
+}
+
+
+fn ebnf_sequence_expr(input: &str) -> IResult<&str, Expr> {
+    fn do_ebnf_sequence_expr(input: &str) -> IResult<&str, Expr> {
+        let (input, _) = multispace1(input)?;
+        let (input, right) = ebnf_factor(input)?;
+        Ok((input, right))
+    }
+
+    let (mut input, left) = ebnf_factor(input)?;
+    let mut factors: Vec<Expr> = vec![left];
+    loop {
+        let Ok((pos, right)) = do_ebnf_sequence_expr(input) else { break };
+        factors.push(right);
+        input = pos;
+    }
+    let result = if factors.len() == 1 {
+        factors.drain(..).next().unwrap()
+    } else {
+        Expr::Sequence(factors.into_iter().map(Rc::new).collect())
+    };
+    Ok((input, result))
+}
+
+
+fn ebnf_alternative_expr(input: &str) -> IResult<&str, Expr> {
+    fn do_ebnf_alternative_expr(input: &str) -> IResult<&str, Expr> {
+        let (input, _) = multispace0(input)?;
+        let (input, _) = char('|')(input)?;
+        let (input, _) = multispace0(input)?;
+        let (input, right) = ebnf_sequence_expr(input)?;
+        Ok((input, right))
+    }
+
+    let (mut input, left) = ebnf_sequence_expr(input)?;
+    let mut elems: Vec<Expr> = vec![left];
+    loop {
+        let Ok((pos, right)) = do_ebnf_alternative_expr(input) else { break };
+        elems.push(right);
+        input = pos;
+    }
+    let result = if elems.len() == 1 {
+        elems.drain(..).next().unwrap()
+    } else {
+        Expr::Alternative(elems.into_iter().map(Rc::new).collect())
+    };
+    Ok((input, result))
+}
+
+
+fn ebnf_rule(input: &str) -> IResult<&str, (Ustr, Rc<Expr>)> {
+    let (input, _) = multispace0(input)?;
+    let (input, name) = ebnf_identifier(input)?;
+    let (input, _) = multispace0(input)?;
+    let (input, _) = tag("::=")(input)?;
+    let (input, _) = multispace0(input)?;
+    let (input, rhs) = ebnf_alternative_expr(input)?;
+    let (input, _) = multispace0(input)?;
+    let (input, _) = char(';')(input)?;
+    let (input, _) = multispace0(input)?;
+    Ok((input, (ustr(name), Rc::new(rhs))))
+}
+
+
+fn ebnf_grammar(input: &str) -> IResult<&str, Vec<(Ustr, Rc<Expr>)>> {
+    let (input, _) = multispace0(input)?;
+    let (input, rules) = many0(ebnf_rule)(input)?;
+    let (input, _) = multispace0(input)?;
+    Ok((input, rules))
+}
+
+
+// Whether `expr` can match the empty string, used by `is_left_recursive` to decide whether a
+// leftmost check needs to keep looking past a `Sequence`'s first (possibly-nullable) factor.
+fn ebnf_is_nullable(expr: &Expr, rules: &UstrMap<Rc<Expr>>) -> bool {
+    match expr {
+        Expr::Literal(_, _) => false,
+        Expr::Regex(_) => false,
+        Expr::Variable(name) => rules.get(name).map(|rhs| ebnf_is_nullable(rhs, rules)).unwrap_or(false),
+        Expr::Sequence(children) => children.iter().all(|child| ebnf_is_nullable(child, rules)),
+        Expr::Alternative(children) => children.iter().any(|child| ebnf_is_nullable(child, rules)),
+        Expr::Optional(_) => true,
+        Expr::Many1(inner) => ebnf_is_nullable(inner, rules),
+    }
+}
+
+
+// Whether expanding `expr` leftmost-first can reach a reference to `name` again without first
+// consuming a terminal -- the condition the downstream DFA codegen can't handle (it assumes a
+// finite expansion).
+fn ebnf_is_left_recursive(name: Ustr, expr: &Expr, rules: &UstrMap<Rc<Expr>>, visiting: &mut HashSet<Ustr>) -> bool {
+    match expr {
+        Expr::Literal(_, _) => false,
+        Expr::Regex(_) => false,
+        Expr::Variable(var) => {
+            if *var == name {
+                return true;
+            }
+            if !visiting.insert(*var) {
+                return false;
+            }
+            let result = match rules.get(var) {
+                Some(rhs) => ebnf_is_left_recursive(name, rhs, rules, visiting),
+                None => false,
+            };
+            visiting.remove(var);
+            result
+        },
+        Expr::Sequence(children) => {
+            for child in children {
+                if ebnf_is_left_recursive(name, child, rules, visiting) {
+                    return true;
+                }
+                if !ebnf_is_nullable(child, rules) {
+                    break;
+                }
+            }
+            false
+        },
+        Expr::Alternative(children) => children.iter().any(|child| ebnf_is_left_recursive(name, child, rules, visiting)),
+        Expr::Optional(inner) => ebnf_is_left_recursive(name, inner, rules, visiting),
+        Expr::Many1(inner) => ebnf_is_left_recursive(name, inner, rules, visiting),
+    }
+}
+
+
+// Unlike a `VariableDefinition`, a `CallVariant`'s `lhs` already carries the command name, so a
+// leading terminal in the root rule's `rhs` that spells out the rule's own name (the `"darcs"` in
+// `darcs ::= "darcs" command;`) is redundant -- the native DSL never duplicates it there -- and
+// is stripped, in each top-level alternative, before building the `CallVariant`.
+fn strip_leading_command_literal(rhs: &Rc<Expr>, name: Ustr) -> Rc<Expr> {
+    match rhs.as_ref() {
+        Expr::Sequence(children) => {
+            let starts_with_command_name = matches!(children.first().map(Rc::as_ref), Some(Expr::Literal(lit, _)) if *lit == name);
+            if !starts_with_command_name {
+                return Rc::clone(rhs);
+            }
+            match &children[1..] {
+                [only] => Rc::clone(only),
+                rest => Rc::new(Expr::Sequence(rest.to_vec())),
+            }
+        },
+        Expr::Alternative(children) => {
+            Rc::new(Expr::Alternative(children.iter().map(|child| strip_leading_command_literal(child, name)).collect()))
+        },
+        _ => Rc::clone(rhs),
+    }
+}
+
+/// Parses a standard (PEG-flavored) EBNF grammar, e.g.:
+///
+/// ```text
+/// darcs ::= "darcs" command;
+/// command ::= "add" file | "remove" file;
+/// ```
+///
+/// and lowers it into the same AST `parse` produces, so EBNF specs imported from elsewhere
+/// don't need to be hand-rewritten in complgen's own DSL. The first rule becomes the command's
+/// `CallVariant` (minus any leading terminal repeating its own name, see
+/// `strip_leading_command_literal`); every other rule becomes a `VariableDefinition` resolved
+/// against references to its name, exactly as with `<NAME> ::= ...;` in the native syntax.
+pub fn parse_ebnf(input: &str) -> Result<Grammar> {
+    let (input, rules) = match ebnf_grammar(input) {
+        Ok((input, rules)) => (input, rules),
+        Err(e) => return Err(Error::ParsingError(e.to_string())),
+    };
+
+    if !input.is_empty() {
+        return Err(Error::TrailingInput(input.to_owned()));
+    }
+
+    if rules.is_empty() {
+        return Err(Error::EmptyGrammar);
+    }
+
+    let rule_map: UstrMap<Rc<Expr>> = rules.iter().map(|(name, rhs)| (*name, Rc::clone(rhs))).collect();
+
+    for (name, rhs) in &rules {
+        let mut visiting: HashSet<Ustr> = Default::default();
+        if ebnf_is_left_recursive(*name, rhs, &rule_map, &mut visiting) {
+            return Err(Error::ParsingError(format!("left-recursive rule: {name}")));
+        }
+    }
+
+    let statements: Vec<Statement> = rules.into_iter().enumerate().map(|(index, (name, rhs))| {
+        if index == 0 {
+            let rhs = strip_leading_command_literal(&rhs, name);
+            Statement::CallVariant { lhs: name, rhs }
+        } else {
+            Statement::VariableDefinition { symbol: name, rhs }
+        }
+    }).collect();
+
+    Ok(Grammar { statements })
+}
+
+
 #[cfg(test)]
 pub mod tests {
     use std::{rc::Rc, ops::Rem};
@@ -390,7 +763,7 @@ pub mod tests {
     use super::*;
 
     fn arb_literal(inputs: Rc<Vec<Ustr>>) -> BoxedStrategy<Rc<Expr>> {
-        (0..inputs.len()).prop_map(move |index| Rc::new(Literal(ustr(&inputs[index])))).boxed()
+        (0..inputs.len()).prop_map(move |index| Rc::new(Literal(ustr(&inputs[index]), None))).boxed()
     }
 
     fn arb_variable(variables: Rc<Vec<Ustr>>) -> BoxedStrategy<Rc<Expr>> {
@@ -440,8 +813,9 @@ pub mod tests {
 
     pub fn do_arb_match(e: Rc<Expr>, rng: &mut TestRng, max_width: usize, output: &mut Vec<Ustr>) {
         match e.as_ref() {
-            Literal(s) => output.push(*s),
+            Literal(s, _) => output.push(*s),
             Variable(_) => output.push(ustr("anything")),
+            Regex(pattern) => output.push(*pattern),
             Sequence(v) => {
                 for subexpr in v {
                     do_arb_match(Rc::clone(&subexpr), rng, max_width, output);
@@ -482,21 +856,54 @@ pub mod tests {
     fn parses_word_terminal() {
         const INPUT: &str = r#"foo"#;
         let ("", e) = terminal_expr(INPUT).unwrap() else { panic!("parsing error"); };
-        assert_eq!(e, Literal(u("foo")));
+        assert_eq!(e, Literal(u("foo"), None));
     }
 
     #[test]
     fn parses_short_option_terminal() {
         const INPUT: &str = r#"-f"#;
         let ("", e) = terminal_expr(INPUT).unwrap() else { panic!("parsing error"); };
-        assert_eq!(e, Literal(u("-f")));
+        assert_eq!(e, Literal(u("-f"), None));
     }
 
     #[test]
     fn parses_long_option_terminal() {
         const INPUT: &str = r#"--foo"#;
         let ("", e) = terminal_expr(INPUT).unwrap() else { panic!("parsing error"); };
-        assert_eq!(e, Literal(u("--foo")));
+        assert_eq!(e, Literal(u("--foo"), None));
+    }
+
+    #[test]
+    fn parses_terminal_with_description() {
+        const INPUT: &str = r#"--summary "show a summary of changes""#;
+        let ("", e) = terminal_expr(INPUT).unwrap() else { panic!("parsing error"); };
+        assert_eq!(e, Literal(u("--summary"), Some(u("show a summary of changes"))));
+    }
+
+    #[test]
+    fn parses_regex_terminal() {
+        const INPUT: &str = r#"/[0-9]{1,5}/"#;
+        let ("", e) = regex_expr(INPUT).unwrap() else { panic!("parsing error"); };
+        assert_eq!(e, Regex(u("[0-9]{1,5}")));
+    }
+
+    #[test]
+    fn rejects_malformed_regex_terminal() {
+        const INPUT: &str = r#"/[0-9/"#;
+        assert!(regex_expr(INPUT).is_err());
+    }
+
+    #[test]
+    fn rejects_symbol_named_like_the_regex_sentinel() {
+        const INPUT: &str = "<regex:[0-9]+>";
+        assert!(symbol_expr(INPUT).is_err());
+    }
+
+    #[test]
+    fn parses_variable_definition_with_regex_rhs() {
+        const INPUT: &str = r#"<PORT> ::= /[0-9]{1,5}/;"#;
+        let ("", stmt) = variable_definition(INPUT).unwrap() else { panic!("parsing error"); };
+        assert_eq!(stmt, Statement::VariableDefinition { symbol: u("PORT"), rhs: Rc::new(Regex(u("[0-9]{1,5}"))) });
     }
 
     #[test]
@@ -540,8 +947,8 @@ pub mod tests {
         assert_eq!(
             e,
             Alternative(vec![
-                Rc::new(Sequence(vec![Rc::new(Literal(u("a"))), Rc::new(Literal(u("b")))])),
-                Rc::new(Literal(u("c")))
+                Rc::new(Sequence(vec![Rc::new(Literal(u("a"), None)), Rc::new(Literal(u("b"), None))])),
+                Rc::new(Literal(u("c"), None))
             ])
         );
     }
@@ -553,8 +960,8 @@ pub mod tests {
         assert_eq!(
             e,
             Sequence(vec![
-                Rc::new(Literal(u("a"))),
-                Rc::new(Alternative(vec![Rc::new(Literal(u("b"))), Rc::new(Literal(u("c")))])),
+                Rc::new(Literal(u("a"), None)),
+                Rc::new(Alternative(vec![Rc::new(Literal(u("b"), None)), Rc::new(Literal(u("c"), None))])),
             ])
         );
     }
@@ -567,7 +974,7 @@ pub mod tests {
             v,
             Statement::CallVariant {
                 lhs: u("foo"),
-                rhs: Rc::new(Literal(u("bar")))
+                rhs: Rc::new(Literal(u("bar"), None))
             }
         );
     }
@@ -583,8 +990,8 @@ foo baz;
             g,
             Grammar {
                 statements: vec![
-                    Statement::CallVariant { lhs: u("foo"), rhs: Rc::new(Literal(u("bar"))) },
-                    Statement::CallVariant { lhs: u("foo"), rhs: Rc::new(Literal(u("baz"))) }
+                    Statement::CallVariant { lhs: u("foo"), rhs: Rc::new(Literal(u("bar"), None)) },
+                    Statement::CallVariant { lhs: u("foo"), rhs: Rc::new(Literal(u("baz"), None)) }
                 ],
             }
         );
@@ -600,15 +1007,15 @@ foo baz;
             Grammar {
                 statements: vec![
                     Statement::CallVariant { lhs: u("darcs"), rhs: Rc::new(Sequence(vec![
-                    Rc::new(Literal(u("help"))),
+                    Rc::new(Literal(u("help"), None)),
                     Rc::new(Sequence(vec![
                         Rc::new(Many1(Rc::new(Alternative(vec![
-                            Rc::new(Alternative(vec![Rc::new(Literal(u("-v"))), Rc::new(Literal(u("--verbose")))])),
-                            Rc::new(Alternative(vec![Rc::new(Literal(u("-q"))), Rc::new(Literal(u("--quiet")))])),
+                            Rc::new(Alternative(vec![Rc::new(Literal(u("-v"), None)), Rc::new(Literal(u("--verbose"), None))])),
+                            Rc::new(Alternative(vec![Rc::new(Literal(u("-q"), None)), Rc::new(Literal(u("--quiet"), None))])),
                         ],)),)),
                         Rc::new(Optional(Rc::new(Sequence(vec![
                             Rc::new(Variable(u("DARCS_COMMAND"))),
-                            Rc::new(Optional(Rc::new(Literal(u("DARCS_SUBCOMMAND"))))),
+                            Rc::new(Optional(Rc::new(Literal(u("DARCS_SUBCOMMAND"), None)))),
                         ])))),
                     ])),
                 ])) },
@@ -671,10 +1078,99 @@ grep [<OPTION>]... <PATTERNS> [<FILE>]...;
             Grammar {
                 statements: vec![
                     Statement::CallVariant { lhs: u("grep"), rhs: Rc::new(Sequence(vec![Rc::new(Many1(Rc::new(Optional(Rc::new(Variable(ustr("OPTION"))))))), Rc::new(Sequence(vec![Rc::new(Variable(ustr("PATTERNS"))), Rc::new(Many1(Rc::new(Optional(Rc::new(Variable(ustr("FILE")))))))]))])) },
-                    Statement::VariableDefinition { symbol: u("OPTION"), rhs: Rc::new(Sequence(vec![Rc::new(Literal(ustr("--color"))), Rc::new(Variable(ustr("WHEN")))])) },
-                    Statement::VariableDefinition { symbol: u("WHEN"), rhs: Rc::new(Alternative(vec![Rc::new(Literal(ustr("always"))), Rc::new(Literal(ustr("never"))), Rc::new(Literal(ustr("auto")))])) },
+                    Statement::VariableDefinition { symbol: u("OPTION"), rhs: Rc::new(Sequence(vec![Rc::new(Literal(ustr("--color"), None)), Rc::new(Variable(ustr("WHEN")))])) },
+                    Statement::VariableDefinition { symbol: u("WHEN"), rhs: Rc::new(Alternative(vec![Rc::new(Literal(ustr("always"), None)), Rc::new(Literal(ustr("never"), None)), Rc::new(Literal(ustr("auto"), None))])) },
+                ],
+            }
+        );
+    }
+
+    #[test]
+    fn ebnf_parses_terminal_and_variable() {
+        assert_eq!(ebnf_terminal_expr(r#""foo""#).unwrap().1, Literal(u("foo"), None));
+        assert_eq!(ebnf_variable_expr("foo").unwrap().1, Variable(u("foo")));
+    }
+
+    #[test]
+    fn ebnf_parses_postfix_operators() {
+        assert_eq!(ebnf_factor(r#""a"+"#).unwrap().1, Many1(Rc::new(Literal(u("a"), None))));
+        assert_eq!(ebnf_factor(r#""a"?"#).unwrap().1, Optional(Rc::new(Literal(u("a"), None))));
+        assert_eq!(ebnf_factor(r#""a"*"#).unwrap().1, Optional(Rc::new(Many1(Rc::new(Literal(u("a"), None))))));
+    }
+
+    #[test]
+    fn ebnf_parses_grammar() {
+        const INPUT: &str = r#"
+darcs ::= "darcs" command;
+command ::= "add" file | "remove" file;
+"#;
+        let g = parse_ebnf(INPUT).unwrap();
+        assert_eq!(
+            g,
+            Grammar {
+                statements: vec![
+                    Statement::CallVariant { lhs: u("darcs"), rhs: Rc::new(Variable(u("command"))) },
+                    Statement::VariableDefinition {
+                        symbol: u("command"),
+                        rhs: Rc::new(Alternative(vec![
+                            Rc::new(Sequence(vec![Rc::new(Literal(u("add"), None)), Rc::new(Variable(u("file")))])),
+                            Rc::new(Sequence(vec![Rc::new(Literal(u("remove"), None)), Rc::new(Variable(u("file")))])),
+                        ])),
+                    },
+                ],
+            }
+        );
+    }
+
+    #[test]
+    fn ebnf_strips_command_literal_from_every_root_alternative() {
+        const INPUT: &str = r#"
+darcs ::= "darcs" "add" | "darcs" "remove";
+"#;
+        let g = parse_ebnf(INPUT).unwrap();
+        assert_eq!(
+            g,
+            Grammar {
+                statements: vec![
+                    Statement::CallVariant {
+                        lhs: u("darcs"),
+                        rhs: Rc::new(Alternative(vec![
+                            Rc::new(Literal(u("add"), None)),
+                            Rc::new(Literal(u("remove"), None)),
+                        ])),
+                    },
                 ],
             }
         );
     }
+
+    #[test]
+    fn ebnf_rejects_left_recursive_rule() {
+        const INPUT: &str = r#"
+expr ::= expr "+" term | term;
+term ::= "1";
+"#;
+        assert!(parse_ebnf(INPUT).is_err());
+    }
+
+    #[test]
+    fn validates_grammar_with_dependent_variable_definitions() {
+        const INPUT: &str = r#"
+grep [<OPTION>]...;
+<OPTION> ::= (--color [<WHEN>]) | --extended-regexp;
+<WHEN> ::= always | never | auto;
+"#;
+        let validated = parse(INPUT).unwrap().validate().unwrap();
+        assert_eq!(validated.command, u("grep"));
+    }
+
+    #[test]
+    fn validate_rejects_circular_variable_definitions() {
+        const INPUT: &str = r#"
+foo <A>;
+<A> ::= <B>;
+<B> ::= <A>;
+"#;
+        assert!(parse(INPUT).unwrap().validate().is_err());
+    }
 }
@@ -0,0 +1,137 @@
+mod bash;
+mod codegen_common;
+mod complete;
+mod dfa;
+mod elvish;
+mod error;
+mod fish;
+mod golden;
+mod grammar;
+mod man;
+mod powershell;
+mod regex;
+mod zsh;
+
+use std::io::Write as _;
+use std::path::PathBuf;
+
+use clap::{Parser, Subcommand};
+
+use crate::complete::Shell;
+
+#[derive(Parser)]
+#[command(name = "complgen")]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Generate a static shell completion script for a grammar file
+    Aot {
+        #[arg(long)]
+        shell: Shell,
+        usage_file: PathBuf,
+        #[arg(long)]
+        output_file: Option<PathBuf>,
+    },
+
+    /// Look up completions for an already-typed command line. Called back by the tiny
+    /// per-shell stub emitted alongside a precompiled DFA, instead of a fully generated script.
+    Complete {
+        #[arg(long)]
+        shell: Shell,
+        #[arg(long)]
+        index: usize,
+        /// Separator used to split a `{ command }` nonterminal's stdout into candidates.
+        /// Candidates printed back to the calling shell stub are always newline-separated,
+        /// regardless of this value -- that's the fixed wire format between this subcommand
+        /// and the stub, not something the grammar author controls.
+        #[arg(long, default_value = "\n")]
+        ifs: String,
+        dfa_file: PathBuf,
+        #[arg(last = true)]
+        words: Vec<String>,
+    },
+
+    /// Generate a man-page SYNOPSIS and short/long options overview from a grammar file
+    Man {
+        usage_file: PathBuf,
+        #[arg(long)]
+        output_file: Option<PathBuf>,
+    },
+
+    /// Precompile a grammar file's DFA to `dfa_file` and emit the tiny per-shell stub that
+    /// shells out to `complgen complete` against it at completion time, instead of a fully
+    /// generated script (see `Aot`).
+    Register {
+        #[arg(long)]
+        shell: Shell,
+        usage_file: PathBuf,
+        dfa_file: PathBuf,
+        #[arg(long)]
+        output_file: Option<PathBuf>,
+    },
+}
+
+fn main() -> anyhow::Result<()> {
+    let cli = Cli::parse();
+    match cli.command {
+        Command::Aot { shell, usage_file, output_file } => {
+            let input = std::fs::read_to_string(&usage_file)?;
+            let validated = grammar::parse(&input)?.validate()?;
+            let arena = bumpalo::Bump::new();
+            let augmented_regex = regex::AugmentedRegex::from_expr(&validated.expr, &arena);
+            let dfa = dfa::DFA::from_regex(&augmented_regex).minimize();
+            let command = validated.command.to_string();
+
+            let mut buffer = String::new();
+            match shell {
+                Shell::Bash => bash::write_completion_script(&mut buffer, &command, &dfa)?,
+                Shell::Fish => fish::write_completion_script(&mut buffer, &command, &dfa)?,
+                Shell::Zsh => zsh::write_completion_script(&mut buffer, &command, &dfa)?,
+                Shell::Elvish => elvish::write_completion_script(&mut buffer, &command, &dfa)?,
+                Shell::Powershell => powershell::write_completion_script(&mut buffer, &command, &dfa)?,
+            }
+
+            match output_file {
+                Some(path) => std::fs::write(path, buffer)?,
+                None => print!("{}", buffer),
+            }
+            Ok(())
+        },
+        Command::Complete { shell, index, ifs, dfa_file, words } => {
+            let dfa = dfa::DFA::load(&dfa_file)?;
+            complete::run_complete_subcommand(&dfa, shell, index, &words, &ifs)
+        },
+        Command::Man { usage_file, output_file } => {
+            let input = std::fs::read_to_string(&usage_file)?;
+            let validated = grammar::parse(&input)?.validate()?;
+            let mut buffer = String::new();
+            man::write_man_page(&mut buffer, &validated)?;
+            match output_file {
+                Some(path) => std::fs::write(path, buffer)?,
+                None => print!("{}", buffer),
+            }
+            Ok(())
+        },
+        Command::Register { shell, usage_file, dfa_file, output_file } => {
+            let input = std::fs::read_to_string(&usage_file)?;
+            let validated = grammar::parse(&input)?.validate()?;
+            let arena = bumpalo::Bump::new();
+            let augmented_regex = regex::AugmentedRegex::from_expr(&validated.expr, &arena);
+            let dfa = dfa::DFA::from_regex(&augmented_regex).minimize();
+            dfa.save(&dfa_file)?;
+            let command = validated.command.to_string();
+
+            let mut buffer: Vec<u8> = Vec::new();
+            complete::write_registration_stub(&mut buffer, shell, &command, &dfa_file)?;
+            match output_file {
+                Some(path) => std::fs::write(path, buffer)?,
+                None => std::io::stdout().write_all(&buffer)?,
+            }
+            Ok(())
+        },
+    }
+}
@@ -0,0 +1,290 @@
+use std::fmt::Write;
+use std::rc::Rc;
+
+use ustr::{Ustr, UstrMap};
+
+use crate::error::Result;
+use crate::grammar::{Expr, Validated};
+
+
+// --- man(7) SYNOPSIS rendering ---
+
+// Man-page convention: literals (subcommands, options) are bolded, nonterminals italicized,
+// optional branches bracketed, and a `Many1` repeated argument suffixed with `...`.
+fn write_expr<W: Write>(buffer: &mut W, expr: &Expr) -> Result<()> {
+    match expr {
+        Expr::Literal(literal, _) => write!(buffer, r"\fB{}\fR", literal)?,
+        Expr::Variable(name) => write!(buffer, r"\fI{}\fR", name)?,
+        Expr::Regex(pattern) => write!(buffer, r"\fI/{}/\fR", pattern)?,
+        Expr::Sequence(children) => {
+            for (i, child) in children.iter().enumerate() {
+                if i > 0 {
+                    write!(buffer, " ")?;
+                }
+                write_expr(buffer, child)?;
+            }
+        },
+        Expr::Alternative(children) => {
+            write!(buffer, "(")?;
+            for (i, child) in children.iter().enumerate() {
+                if i > 0 {
+                    write!(buffer, " | ")?;
+                }
+                write_expr(buffer, child)?;
+            }
+            write!(buffer, ")")?;
+        },
+        Expr::Optional(child) => {
+            write!(buffer, "[")?;
+            write_expr(buffer, child)?;
+            write!(buffer, "]")?;
+        },
+        Expr::Many1(child) => {
+            write_expr(buffer, child)?;
+            write!(buffer, " ...")?;
+        },
+    }
+    Ok(())
+}
+
+// `Grammar::validate()` folds every `CallVariant` sharing the command's name into one top-level
+// `Alternative`, so its immediate children are the per-variant usage lines we want one SYNOPSIS
+// entry each for. A grammar with only a single variant never gets wrapped in one, so when that
+// variant's own expression is a top-level `Alternative` (e.g. `foo (bar | baz);`), each of its
+// alternatives becomes a separate entry instead.
+fn synopsis_variants(expr: &Rc<Expr>) -> Vec<Rc<Expr>> {
+    match expr.as_ref() {
+        Expr::Alternative(children) => children.clone(),
+        _ => vec![Rc::clone(expr)],
+    }
+}
+
+/// Emits a `.SH SYNOPSIS` section with one usage line per top-level `CallVariant`; a lone
+/// `CallVariant` whose expression is itself a top-level alternative gets one line per alternative.
+pub fn write_man_synopsis<W: Write>(buffer: &mut W, validated: &Validated) -> Result<()> {
+    writeln!(buffer, ".SH SYNOPSIS")?;
+    for variant in synopsis_variants(&validated.expr) {
+        write!(buffer, r"\fB{}\fR ", validated.command)?;
+        write_expr(buffer, &variant)?;
+        writeln!(buffer)?;
+        writeln!(buffer, ".br")?;
+    }
+    Ok(())
+}
+
+
+// --- Short/long option index ---
+
+fn is_short_option(s: &str) -> bool {
+    s.len() == 2 && s.starts_with('-') && !s.starts_with("--")
+}
+
+fn is_long_option(s: &str) -> bool {
+    s.len() > 2 && s.starts_with("--")
+}
+
+// The first literal reached while descending into a variant, used as a human-readable label
+// for which subcommand an option was found under (e.g. `add` in `darcs add [--case-ok]`).
+fn leading_literal(expr: &Expr) -> Option<Ustr> {
+    match expr {
+        Expr::Literal(literal, _) => Some(*literal),
+        Expr::Sequence(children) => children.first().and_then(|child| leading_literal(child)),
+        Expr::Optional(child) | Expr::Many1(child) => leading_literal(child),
+        Expr::Alternative(_) | Expr::Variable(_) | Expr::Regex(_) => None,
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OptionPairing {
+    pub short: Option<Ustr>,
+    pub long: Option<Ustr>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OptionConflict {
+    /// The option string that is paired differently depending on the subcommand.
+    pub option: Ustr,
+    pub subcommands: Vec<Ustr>,
+}
+
+#[derive(Debug, Default)]
+pub struct OptionIndex {
+    pub pairings: Vec<OptionPairing>,
+    pub conflicts: Vec<OptionConflict>,
+}
+
+// Looks for the `( -x | --xxx )`-style alternative complgen grammars spell options with, and
+// records any lone short or long option found outside of such a pairing too, so a later pass
+// can flag options missing their other half.
+fn visit_options(expr: &Expr, subcommand: Ustr, found: &mut Vec<(Ustr, OptionPairing)>) {
+    match expr {
+        Expr::Literal(literal, _) => {
+            if is_short_option(literal) {
+                found.push((subcommand, OptionPairing { short: Some(*literal), long: None }));
+            } else if is_long_option(literal) {
+                found.push((subcommand, OptionPairing { short: None, long: Some(*literal) }));
+            }
+        },
+        Expr::Variable(_) | Expr::Regex(_) => {},
+        Expr::Alternative(children) => {
+            if let [left, right] = children.as_slice() {
+                if let (Expr::Literal(a, _), Expr::Literal(b, _)) = (left.as_ref(), right.as_ref()) {
+                    let pairing = if is_short_option(a) && is_long_option(b) {
+                        Some(OptionPairing { short: Some(*a), long: Some(*b) })
+                    } else if is_short_option(b) && is_long_option(a) {
+                        Some(OptionPairing { short: Some(*b), long: Some(*a) })
+                    } else {
+                        None
+                    };
+                    if let Some(pairing) = pairing {
+                        found.push((subcommand, pairing));
+                        return;
+                    }
+                }
+            }
+            for child in children {
+                visit_options(child, subcommand, found);
+            }
+        },
+        Expr::Sequence(children) => {
+            for child in children {
+                visit_options(child, subcommand, found);
+            }
+        },
+        Expr::Optional(child) | Expr::Many1(child) => visit_options(child, subcommand, found),
+    }
+}
+
+fn pairing_sort_key(pairing: &OptionPairing) -> (&str, &str) {
+    (pairing.short.as_deref().unwrap_or(""), pairing.long.as_deref().unwrap_or(""))
+}
+
+/// Walks every `CallVariant` usage line, collapsing every `( -x | --xxx )` alternative into a
+/// deduplicated short/long pairing and flagging any option string that is spelled out
+/// differently depending on the subcommand it appears under.
+pub fn build_option_index(validated: &Validated) -> OptionIndex {
+    let mut found: Vec<(Ustr, OptionPairing)> = Default::default();
+    for variant in synopsis_variants(&validated.expr) {
+        let subcommand = leading_literal(&variant).unwrap_or(validated.command);
+        visit_options(&variant, subcommand, &mut found);
+    }
+
+    let mut pairings: Vec<OptionPairing> = Default::default();
+    for (_, pairing) in &found {
+        if !pairings.contains(pairing) {
+            pairings.push(pairing.clone());
+        }
+    }
+    pairings.sort_by_key(pairing_sort_key);
+
+    let mut by_option: UstrMap<Vec<(Ustr, OptionPairing)>> = Default::default();
+    for (subcommand, pairing) in &found {
+        if let Some(short) = pairing.short {
+            by_option.entry(short).or_default().push((*subcommand, pairing.clone()));
+        }
+        if let Some(long) = pairing.long {
+            by_option.entry(long).or_default().push((*subcommand, pairing.clone()));
+        }
+    }
+
+    let mut conflicts: Vec<OptionConflict> = Default::default();
+    for (option, occurrences) in by_option {
+        let mut distinct_pairings: Vec<&OptionPairing> = Default::default();
+        for (_, pairing) in &occurrences {
+            if !distinct_pairings.contains(&pairing) {
+                distinct_pairings.push(pairing);
+            }
+        }
+        if distinct_pairings.len() > 1 {
+            let subcommands = occurrences.iter().map(|(subcommand, _)| *subcommand).collect();
+            conflicts.push(OptionConflict { option, subcommands });
+        }
+    }
+    conflicts.sort_by_key(|conflict| conflict.option);
+
+    OptionIndex { pairings, conflicts }
+}
+
+/// Renders the option index as a plain two-column table, followed by any conflicting spellings.
+pub fn write_option_index<W: Write>(buffer: &mut W, index: &OptionIndex) -> Result<()> {
+    writeln!(buffer, "{:<6} {}", "SHORT", "LONG")?;
+    for pairing in &index.pairings {
+        let short = pairing.short.map(|s| s.to_string()).unwrap_or_default();
+        let long = pairing.long.map(|s| s.to_string()).unwrap_or_default();
+        if short.is_empty() {
+            writeln!(buffer, "{:<6} {} (no short form)", "", long)?;
+        } else if long.is_empty() {
+            writeln!(buffer, "{:<6} (no long form)", short)?;
+        } else {
+            writeln!(buffer, "{:<6} {}", short, long)?;
+        }
+    }
+
+    if !index.conflicts.is_empty() {
+        writeln!(buffer)?;
+        writeln!(buffer, "conflicting spellings:")?;
+        for conflict in &index.conflicts {
+            let subcommands = conflict.subcommands.iter().map(|s| s.as_str()).collect::<Vec<_>>().join(", ");
+            writeln!(buffer, "  {} is spelled differently across: {}", conflict.option, subcommands)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Generates a full `SYNOPSIS` + options-overview man-page fragment for a validated grammar.
+pub fn write_man_page<W: Write>(buffer: &mut W, validated: &Validated) -> Result<()> {
+    write_man_synopsis(buffer, validated)?;
+    writeln!(buffer, ".SH OPTIONS")?;
+    let index = build_option_index(validated);
+    write_option_index(buffer, &index)?;
+    Ok(())
+}
+
+
+#[cfg(test)]
+mod tests {
+    use ustr::ustr as u;
+
+    use super::*;
+    use crate::grammar;
+
+    fn validate(input: &str) -> Validated {
+        grammar::parse(input).unwrap().validate().unwrap()
+    }
+
+    #[test]
+    fn synopsis_has_one_line_per_call_variant() {
+        let validated = validate("foo bar; foo baz;");
+        let mut buffer = String::new();
+        write_man_synopsis(&mut buffer, &validated).unwrap();
+        assert_eq!(buffer, ".SH SYNOPSIS\n\\fBfoo\\fR \\fBbar\\fR\n.br\n\\fBfoo\\fR \\fBbaz\\fR\n.br\n");
+    }
+
+    #[test]
+    fn option_index_pairs_short_and_long_forms() {
+        let validated = validate("grep ( -v | --invert-match ) <PATTERN>;");
+        let index = build_option_index(&validated);
+        assert_eq!(index.pairings, vec![
+            OptionPairing { short: Some(u("-v")), long: Some(u("--invert-match")) },
+        ]);
+        assert!(index.conflicts.is_empty());
+    }
+
+    #[test]
+    fn option_index_flags_long_option_without_short_form() {
+        let validated = validate("grep --only-matching <PATTERN>;");
+        let index = build_option_index(&validated);
+        assert_eq!(index.pairings, vec![
+            OptionPairing { short: None, long: Some(u("--only-matching")) },
+        ]);
+    }
+
+    #[test]
+    fn option_index_flags_conflicting_spelling_across_subcommands() {
+        let validated = validate("darcs ( ( -v | --verbose ) ); darcs ( ( -v | --loud ) );");
+        let index = build_option_index(&validated);
+        assert_eq!(index.conflicts.len(), 1);
+        assert_eq!(index.conflicts[0].option, u("-v"));
+    }
+}
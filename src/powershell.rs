@@ -0,0 +1,117 @@
+use std::fmt::Write;
+
+use complgen::StateId;
+
+use crate::error::Result;
+use crate::dfa::DFA;
+use crate::regex::{Input, MatchAnythingInput};
+use crate::codegen_common::{as_regex_pattern, collect_reachable_states};
+
+
+fn write_dfa_state<W: Write>(buffer: &mut W, dfa: &DFA, state: StateId) -> Result<()> {
+    write!(buffer, r#"
+function State{state} {{
+    param($Words, $WordIndex, $WordToComplete)
+
+    # $CommandAst.CommandElements includes the in-progress word once it parses as a token, so
+    # (like Elvish's $words) the cursor is at the final element, not one past it.
+    if ($WordIndex -eq ($Words.Count - 1)) {{
+"#, state = state)?;
+
+    for (input, _) in dfa.get_transitions_from(state) {
+        match input {
+            // Plain strings returned here give PowerShell no way to suppress its own trailing
+            // space (that needs a CompletionResult object, which the static generator doesn't
+            // build), so a literal ending in '=' isn't special-cased the way it is in
+            // bash.rs/zsh.rs/elvish.rs.
+            Input::Literal(literal, _) => {
+                write!(buffer, r#"        "{literal}"
+"#, literal = literal)?;
+            },
+            Input::Any(MatchAnythingInput::Nonterminal(nonterm)) if nonterm.as_str() == "PATH" => {
+                write!(buffer, r#"        Get-ChildItem -Path "$WordToComplete*" | Select-Object -ExpandProperty Name
+"#)?;
+            },
+            Input::Any(MatchAnythingInput::Nonterminal(nonterm)) if nonterm.as_str() == "DIRECTORY" => {
+                write!(buffer, r#"        Get-ChildItem -Path "$WordToComplete*" -Directory | Select-Object -ExpandProperty Name
+"#)?;
+            },
+            Input::Any(MatchAnythingInput::Command(command)) => {
+                write!(buffer, r#"        {command}
+"#, command = command)?;
+            },
+            // A regex-typed nonterminal has no enumerable candidate source (see complete.rs's
+            // get_completions_for_input), so it's excluded here and falls through to the plain
+            // "nothing to offer" arm below, same as one with no registered builtin.
+            Input::Any(MatchAnythingInput::Nonterminal(nonterm)) if as_regex_pattern(nonterm.as_str()).is_none() => {
+                if let Some(builtin) = crate::complete::BUILTIN_NONTERMINALS.iter().find(|builtin| builtin.name == nonterm.as_str()) {
+                    write!(buffer, r#"        {powershell}
+"#, powershell = builtin.powershell)?;
+                }
+            },
+            Input::Any(MatchAnythingInput::Nonterminal(_)) => {},
+        }
+    }
+
+    write!(buffer, r#"        return
+    }}
+
+    $Word = $Words[$WordIndex]
+"#)?;
+
+    let mut regex_destination: Option<(String, StateId)> = None;
+    let mut any_destination: Option<StateId> = None;
+    for (input, to) in dfa.get_transitions_from(state) {
+        match input {
+            Input::Literal(literal, _) => {
+                write!(buffer, r#"    if ($Word -eq "{literal}") {{
+        State{to} $Words ($WordIndex + 1) $WordToComplete
+        return
+    }}
+"#, literal = literal, to = to)?;
+            },
+            Input::Any(MatchAnythingInput::Nonterminal(nonterm)) if as_regex_pattern(nonterm.as_str()).is_some() => {
+                let pattern = as_regex_pattern(nonterm.as_str()).unwrap().to_string();
+                regex_destination.get_or_insert((pattern, to));
+            },
+            Input::Any(_) => {
+                any_destination.get_or_insert(to);
+            },
+        }
+    }
+
+    if let Some((pattern, to)) = &regex_destination {
+        // .NET regex (used by `-match`) supports `(?:...)`, unlike bash/zsh's POSIX ERE.
+        write!(buffer, r#"    if ($Word -match "^(?:{pattern})$") {{
+        State{to} $Words ($WordIndex + 1) $WordToComplete
+        return
+    }}
+"#, pattern = pattern, to = to)?;
+    }
+
+    if let Some(to) = any_destination {
+        write!(buffer, r#"    State{to} $Words ($WordIndex + 1) $WordToComplete
+"#, to = to)?;
+    }
+
+    write!(buffer, r#"}}
+"#)?;
+    Ok(())
+}
+
+
+pub fn write_completion_script<W: Write>(buffer: &mut W, command: &str, dfa: &DFA) -> Result<()> {
+    for state in collect_reachable_states(dfa) {
+        write_dfa_state(buffer, dfa, state)?;
+    }
+
+    write!(buffer, r#"
+Register-ArgumentCompleter -Native -CommandName {command} -ScriptBlock {{
+    param($WordToComplete, $CommandAst, $CursorPosition)
+    $CommandWords = $CommandAst.CommandElements | ForEach-Object {{ $_.ToString() }}
+    State{starting_state} $CommandWords 1 $WordToComplete
+}}
+"#, command = command, starting_state = dfa.starting_state)?;
+
+    Ok(())
+}
@@ -0,0 +1,127 @@
+use std::fmt::Write;
+
+use complgen::StateId;
+
+use crate::error::Result;
+use crate::dfa::DFA;
+use crate::regex::{Input, MatchAnythingInput};
+use crate::codegen_common::{as_regex_pattern, collect_reachable_states};
+
+
+// `_describe` expects an array of "value:description" strings and renders the description
+// column in the completion menu via `compadd -d` under the hood.
+fn write_dfa_state<W: Write>(buffer: &mut W, dfa: &DFA, state: StateId) -> Result<()> {
+    write!(buffer, r#"
+_state_{state} () {{
+    if (( CURRENT == $__complgen_word_index )); then
+        local -a candidates nospace_candidates
+"#, state = state)?;
+
+    for (input, _) in dfa.get_transitions_from(state) {
+        match input {
+            // A literal ending in '=' (e.g. a `--foo=`-shaped option) shouldn't get a
+            // trailing space inserted after it, so it goes through `compadd -S ''` below
+            // instead of through `_describe`, which always appends one.
+            Input::Literal(literal, _) if literal.ends_with('=') => {
+                write!(buffer, r#"        nospace_candidates+=('{literal}')
+"#, literal = literal)?;
+            },
+            Input::Literal(literal, description) => {
+                let description = description.unwrap_or_default();
+                write!(buffer, r#"        candidates+=('{literal}:{description}')
+"#, literal = literal, description = description)?;
+            },
+            Input::Any(MatchAnythingInput::Nonterminal(nonterm)) if nonterm.as_str() == "PATH" => {
+                write!(buffer, r#"        _path_files
+"#)?;
+            },
+            Input::Any(MatchAnythingInput::Nonterminal(nonterm)) if nonterm.as_str() == "DIRECTORY" => {
+                write!(buffer, r#"        _path_files -/
+"#)?;
+            },
+            Input::Any(MatchAnythingInput::Command(command)) => {
+                write!(buffer, r#"        candidates+=($({command}))
+"#, command = command)?;
+            },
+            // A regex-typed nonterminal has no enumerable candidate source (see complete.rs's
+            // get_completions_for_input), so it's excluded here and falls through to the plain
+            // "nothing to offer" arm below, same as one with no registered builtin.
+            Input::Any(MatchAnythingInput::Nonterminal(nonterm)) if as_regex_pattern(nonterm.as_str()).is_none() => {
+                if let Some(builtin) = crate::complete::BUILTIN_NONTERMINALS.iter().find(|builtin| builtin.name == nonterm.as_str()) {
+                    write!(buffer, r#"        {zsh}
+"#, zsh = builtin.zsh)?;
+                }
+            },
+            Input::Any(MatchAnythingInput::Nonterminal(_)) => {},
+        }
+    }
+
+    write!(buffer, r#"        _describe 'values' candidates
+        compadd -S '' -a nospace_candidates
+        return
+    fi
+
+    local word="$words[$__complgen_word_index]"
+"#)?;
+
+    let mut regex_destination: Option<(String, StateId)> = None;
+    let mut any_destination: Option<StateId> = None;
+    for (input, to) in dfa.get_transitions_from(state) {
+        match input {
+            Input::Literal(literal, _) => {
+                write!(buffer, r#"    if [[ "$word" == "{literal}" ]]; then
+        __complgen_word_index=$((__complgen_word_index+1))
+        _state_{to}
+        return
+    fi
+"#, literal = literal, to = to)?;
+            },
+            Input::Any(MatchAnythingInput::Nonterminal(nonterm)) if as_regex_pattern(nonterm.as_str()).is_some() => {
+                let pattern = as_regex_pattern(nonterm.as_str()).unwrap().to_string();
+                regex_destination.get_or_insert((pattern, to));
+            },
+            Input::Any(_) => {
+                any_destination.get_or_insert(to);
+            },
+        }
+    }
+
+    if let Some((pattern, to)) = &regex_destination {
+        // zsh's `=~` is POSIX ERE, same as bash -- plain `(...)` groups, no `(?:...)`.
+        write!(buffer, r#"    if [[ "$word" =~ ^({pattern})$ ]]; then
+        __complgen_word_index=$((__complgen_word_index+1))
+        _state_{to}
+        return
+    fi
+"#, pattern = pattern, to = to)?;
+    }
+
+    if let Some(to) = any_destination {
+        write!(buffer, r#"    __complgen_word_index=$((__complgen_word_index+1))
+    _state_{to}
+"#, to = to)?;
+    }
+
+    write!(buffer, r#"}}
+"#)?;
+    Ok(())
+}
+
+
+pub fn write_completion_script<W: Write>(buffer: &mut W, command: &str, dfa: &DFA) -> Result<()> {
+    for state in collect_reachable_states(dfa) {
+        write_dfa_state(buffer, dfa, state)?;
+    }
+
+    write!(buffer, r#"
+_{command}_complete () {{
+    # $words is 1-based and $words[1] is the command itself, so start past it.
+    local __complgen_word_index=2
+    _state_{starting_state}
+}}
+
+compdef _{command}_complete {command}
+"#, command = command, starting_state = dfa.starting_state)?;
+
+    Ok(())
+}